@@ -53,14 +53,14 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let eval = client.get_config_evaluation("test_config", &user).await?;
     println!("Evaluation: {} -> {:?}", eval.name, eval.group);
 
-    // Event logging
-    client.log_event("page_view", &user).await?;
+    // Event logging (queued and flushed in the background)
+    client.log_event("page_view", &user)?;
 
     let mut metadata = HashMap::new();
     metadata.insert("product_id".to_string(), "prod_456".to_string());
 
     client
-        .log_events(
+        .log_events_immediately(
             vec![
                 StatsigEvent::builder()
                     .event_name("add_to_cart")
@@ -74,5 +74,8 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     println!("Events logged successfully");
 
+    // Flush queued events before exiting
+    client.shutdown().await;
+
     Ok(())
 }
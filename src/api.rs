@@ -1,4 +1,7 @@
+use crate::error::StatsigError;
+use crate::events::ExposureEventMetadata;
 use serde::{Deserialize, Serialize};
+use std::time::{Duration, SystemTime};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct StatsigMetadata {
@@ -20,6 +23,10 @@ pub struct GateEvaluationResult {
     pub rule_id: Option<String>,
     #[serde(rename = "group_name")]
     pub group_name: Option<String>,
+    #[serde(default)]
+    pub secondary_exposures: Vec<ExposureEventMetadata>,
+    #[serde(skip)]
+    pub reason: EvaluationReason,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -31,6 +38,142 @@ pub struct ConfigEvaluationResult {
     #[serde(rename = "group_name")]
     pub group_name: Option<String>,
     pub group: Option<String>,
+    #[serde(default)]
+    pub secondary_exposures: Vec<ExposureEventMetadata>,
+    #[serde(skip)]
+    pub reason: EvaluationReason,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LayerEvaluationResult {
+    pub name: String,
+    pub value: serde_json::Value,
+    #[serde(rename = "rule_id")]
+    pub rule_id: Option<String>,
+    #[serde(rename = "group_name", default)]
+    pub group_name: Option<String>,
+    #[serde(rename = "allocated_experiment_name", default)]
+    pub allocated_experiment_name: Option<String>,
+    #[serde(default)]
+    pub explicit_parameters: Vec<String>,
+    #[serde(default)]
+    pub secondary_exposures: Vec<ExposureEventMetadata>,
+    #[serde(default)]
+    pub undelegated_secondary_exposures: Vec<ExposureEventMetadata>,
+    #[serde(skip)]
+    pub reason: EvaluationReason,
+}
+
+/// Kind of entity an evaluation is for
+#[derive(Debug, Clone, Hash, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EntityType {
+    Gate,
+    /// Dynamic configs and experiments
+    Config,
+    Layer,
+}
+
+impl EntityType {
+    /// Lowercase name used in cache store keys
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Gate => "gate",
+            Self::Config => "config",
+            Self::Layer => "layer",
+        }
+    }
+}
+
+/// A cached evaluation, as returned by `StatsigClient::cache_entries`
+#[derive(Debug, Clone)]
+pub struct CacheEntry {
+    pub entity_type: EntityType,
+    pub name: String,
+    /// Canonical user fingerprint from `User::hash_for_cache`
+    pub user_hash: String,
+    /// Time since the evaluation was fetched
+    pub age: Duration,
+    /// Gate value as a JSON boolean, or the config or layer value
+    pub value: serde_json::Value,
+}
+
+/// Where an evaluation result was served from
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EvaluationReason {
+    /// Fetched from the Statsig API
+    #[default]
+    Network,
+    /// Evaluated in-process from downloaded config specs
+    LocalEvaluation,
+    /// Served from a fresh cache entry
+    Cache,
+    /// Last-known value served because the Statsig API was unavailable
+    Fallback,
+    /// Configured default served because the Statsig API was unavailable
+    Default,
+    /// Forced by a local override
+    Override,
+    /// Seeded from bootstrap data at startup
+    Bootstrap,
+    /// Configured default served because evaluation failed
+    Error,
+}
+
+impl EvaluationReason {
+    /// Whether the value is a configured default rather than an evaluation
+    pub(crate) fn is_default(self) -> bool {
+        matches!(self, Self::Default | Self::Error)
+    }
+
+    /// Reason reported when a result with this reason is served from the cache
+    pub(crate) fn served_from_cache(self) -> Self {
+        match self {
+            Self::Bootstrap => Self::Bootstrap,
+            _ => Self::Cache,
+        }
+    }
+
+    /// Returns `true` if the result was served in degraded (offline fallback) mode
+    pub fn is_fallback(&self) -> bool {
+        matches!(self, Self::Fallback | Self::Default)
+    }
+}
+
+/// An evaluated value with where it came from and how fresh it is
+///
+/// Returned by `StatsigClient::check_gate_with_details` and
+/// `StatsigClient::get_config_with_details`.
+#[derive(Debug, Clone)]
+pub struct EvaluationDetails<T> {
+    pub value: T,
+    pub reason: EvaluationReason,
+    pub rule_id: Option<String>,
+    pub group_name: Option<String>,
+    /// Version of the gate or config definition, known under local evaluation
+    pub config_version: Option<u64>,
+    /// When the value was evaluated; earlier than now for cached values
+    pub evaluation_time: SystemTime,
+    /// Time since the value was fetched, for values served from the cache
+    pub cache_age: Option<Duration>,
+    /// Why evaluation failed, when `reason` is `EvaluationReason::Error`
+    pub error: Option<StatsigError>,
+}
+
+impl<T> EvaluationDetails<T> {
+    pub(crate) fn from_error(value: T, error: StatsigError) -> Self {
+        Self {
+            value,
+            reason: EvaluationReason::Error,
+            rule_id: None,
+            group_name: None,
+            config_version: None,
+            evaluation_time: SystemTime::now(),
+            cache_age: None,
+            error: Some(error),
+        }
+    }
 }
 
 impl StatsigMetadata {
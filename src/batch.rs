@@ -1,17 +1,20 @@
 //! Batch processing module for optimizing API requests
 //!
-//! This module handles batching multiple gate and config requests into single API calls
+//! This module handles batching multiple gate, config and layer requests into single API calls
 //! to reduce network overhead and improve performance.
 
 use crate::{
-    api::{ConfigEvaluationResult, GateEvaluationResult},
+    api::{ConfigEvaluationResult, GateEvaluationResult, LayerEvaluationResult},
     config::StatsigClientConfig,
-    error::Result,
-    transport::StatsigTransport,
+    error::{Result, StatsigError},
+    transport::Transport,
     user::User,
 };
-use std::collections::HashMap;
-use tokio::sync::{mpsc, oneshot};
+use std::collections::{HashMap, HashSet};
+use std::future::Future;
+use std::sync::Arc;
+use tokio::sync::{Semaphore, mpsc, oneshot};
+use tokio::task::JoinSet;
 use tracing::{error, info};
 
 /// Represents different types of batch requests
@@ -22,13 +25,23 @@ pub enum BatchRequest {
         user: User,
         response_tx: oneshot::Sender<Result<Vec<GateEvaluationResult>>>,
     },
+    /// Responds with one result per requested config, in request order
     GetConfigs {
         config_names: Vec<String>,
         user: User,
-        response_tx: oneshot::Sender<Result<Vec<ConfigEvaluationResult>>>,
+        response_tx: oneshot::Sender<Vec<Result<ConfigEvaluationResult>>>,
+    },
+    /// Responds with one result per requested layer, in request order
+    GetLayers {
+        layer_names: Vec<String>,
+        user: User,
+        response_tx: oneshot::Sender<Vec<Result<LayerEvaluationResult>>>,
     },
 }
 
+/// Entity names, the user they are evaluated for, and where to send results
+type EntityRequest<T> = (Vec<String>, User, oneshot::Sender<Vec<Result<T>>>);
+
 /// Handles batch processing of API requests
 pub struct BatchProcessor {
     receiver: mpsc::Receiver<BatchRequest>,
@@ -48,10 +61,11 @@ impl BatchProcessor {
     }
 
     /// Runs the batch processor loop
-    pub async fn run(mut self, transport: StatsigTransport, config: StatsigClientConfig) {
+    pub async fn run(mut self, transport: Arc<dyn Transport>, config: StatsigClientConfig) {
         let mut interval = tokio::time::interval(config.batch_flush_interval);
         let mut gate_requests = Vec::new();
         let mut config_requests = Vec::new();
+        let mut layer_requests = Vec::new();
 
         loop {
             tokio::select! {
@@ -59,24 +73,45 @@ impl BatchProcessor {
                     match request {
                         BatchRequest::CheckGates { .. } => gate_requests.push(request),
                         BatchRequest::GetConfigs { .. } => config_requests.push(request),
+                        BatchRequest::GetLayers { .. } => layer_requests.push(request),
                     }
 
                     // Process if batch size reached
-                    if gate_requests.len() >= config.batch_size || config_requests.len() >= config.batch_size {
-                        Self::process_gate_batch(&transport, &mut gate_requests).await;
-                        Self::process_config_batch(&transport, &mut config_requests).await;
+                    if gate_requests.len() >= config.batch_size
+                        || config_requests.len() >= config.batch_size
+                        || layer_requests.len() >= config.batch_size
+                    {
+                        Self::process_gate_batch(transport.as_ref(), &mut gate_requests).await;
+                        Self::process_config_batch(&transport, &mut config_requests, config.config_fetch_concurrency).await;
+                        Self::process_layer_batch(&transport, &mut layer_requests, config.config_fetch_concurrency).await;
                     }
                 }
                 _ = interval.tick() => {
                     if !gate_requests.is_empty() {
-                        Self::process_gate_batch(&transport, &mut gate_requests).await;
+                        Self::process_gate_batch(transport.as_ref(), &mut gate_requests).await;
                     }
                     if !config_requests.is_empty() {
-                        Self::process_config_batch(&transport, &mut config_requests).await;
+                        Self::process_config_batch(&transport, &mut config_requests, config.config_fetch_concurrency).await;
+                    }
+                    if !layer_requests.is_empty() {
+                        Self::process_layer_batch(&transport, &mut layer_requests, config.config_fetch_concurrency).await;
                     }
                 }
                 _ = self.shutdown_rx.recv() => {
                     info!("Batch processor shutting down");
+
+                    // Stop accepting requests and drain what is already queued
+                    self.receiver.close();
+                    while let Some(request) = self.receiver.recv().await {
+                        match request {
+                            BatchRequest::CheckGates { .. } => gate_requests.push(request),
+                            BatchRequest::GetConfigs { .. } => config_requests.push(request),
+                            BatchRequest::GetLayers { .. } => layer_requests.push(request),
+                        }
+                    }
+                    Self::process_gate_batch(transport.as_ref(), &mut gate_requests).await;
+                    Self::process_config_batch(&transport, &mut config_requests, config.config_fetch_concurrency).await;
+                    Self::process_layer_batch(&transport, &mut layer_requests, config.config_fetch_concurrency).await;
                     break;
                 }
             }
@@ -84,7 +119,7 @@ impl BatchProcessor {
     }
 
     /// Processes a batch of gate requests
-    async fn process_gate_batch(transport: &StatsigTransport, requests: &mut Vec<BatchRequest>) {
+    async fn process_gate_batch(transport: &dyn Transport, requests: &mut Vec<BatchRequest>) {
         if requests.is_empty() {
             return;
         }
@@ -103,6 +138,8 @@ impl BatchProcessor {
         for (_user_hash, group_requests) in user_groups {
             if let Some(first_request) = group_requests.first() {
                 if let BatchRequest::CheckGates { user, .. } = first_request {
+                    // Deduplicate names requested by several callers, keeping order
+                    let mut seen = HashSet::new();
                     let all_gate_names: Vec<String> = group_requests
                         .iter()
                         .filter_map(|req| {
@@ -113,6 +150,7 @@ impl BatchProcessor {
                             }
                         })
                         .flatten()
+                        .filter(|gate_name| seen.insert(gate_name.clone()))
                         .collect();
 
                     match transport.check_gates(all_gate_names, user).await {
@@ -150,41 +188,139 @@ impl BatchProcessor {
     }
 
     /// Processes a batch of config requests
-    async fn process_config_batch(transport: &StatsigTransport, requests: &mut Vec<BatchRequest>) {
-        if requests.is_empty() {
+    async fn process_config_batch(
+        transport: &Arc<dyn Transport>,
+        requests: &mut Vec<BatchRequest>,
+        concurrency: usize,
+    ) {
+        let batch = std::mem::take(requests)
+            .into_iter()
+            .filter_map(|request| match request {
+                BatchRequest::GetConfigs {
+                    config_names,
+                    user,
+                    response_tx,
+                } => Some((config_names, user, response_tx)),
+                _ => None,
+            })
+            .collect();
+
+        Self::process_entity_batch(
+            transport,
+            batch,
+            concurrency,
+            |transport, name, user| async move { transport.get_config(&name, &user).await },
+        )
+        .await;
+    }
+
+    /// Processes a batch of layer requests
+    async fn process_layer_batch(
+        transport: &Arc<dyn Transport>,
+        requests: &mut Vec<BatchRequest>,
+        concurrency: usize,
+    ) {
+        let batch = std::mem::take(requests)
+            .into_iter()
+            .filter_map(|request| match request {
+                BatchRequest::GetLayers {
+                    layer_names,
+                    user,
+                    response_tx,
+                } => Some((layer_names, user, response_tx)),
+                _ => None,
+            })
+            .collect();
+
+        Self::process_entity_batch(
+            transport,
+            batch,
+            concurrency,
+            |transport, name, user| async move { transport.get_layer(&name, &user).await },
+        )
+        .await;
+    }
+
+    /// Fetches entities that have no multi-entity endpoint
+    ///
+    /// Requests are grouped by user and each distinct entity is fetched once,
+    /// with at most `concurrency` requests in flight across the whole batch.
+    /// Each requester gets a result per entity, so a failed fetch only
+    /// reaches the requesters of that entity.
+    async fn process_entity_batch<T, F, Fut>(
+        transport: &Arc<dyn Transport>,
+        batch: Vec<EntityRequest<T>>,
+        concurrency: usize,
+        fetch: F,
+    ) where
+        T: Clone + Send + 'static,
+        F: Fn(Arc<dyn Transport>, String, User) -> Fut,
+        Fut: Future<Output = Result<T>> + Send + 'static,
+    {
+        if batch.is_empty() {
             return;
         }
 
-        let batch = std::mem::take(requests);
-
-        // Process each config request individually for now (could be optimized)
+        // Group by user for efficiency
+        let mut user_groups: HashMap<String, Vec<EntityRequest<T>>> = HashMap::new();
         for request in batch {
-            if let BatchRequest::GetConfigs {
-                config_names,
-                user,
-                response_tx,
-            } = request
-            {
-                let results = Self::fetch_configs_from_api(transport, &config_names, &user).await;
-                let _ = response_tx.send(results);
+            let user_hash = Self::hash_user_for_batch(&request.1);
+            user_groups.entry(user_hash).or_default().push(request);
+        }
+
+        // Fetch each distinct (user, entity) pair once
+        let semaphore = Arc::new(Semaphore::new(concurrency.max(1)));
+        let mut tasks = JoinSet::new();
+        for (user_hash, group_requests) in &user_groups {
+            let mut seen = HashSet::new();
+            for (names, user, _) in group_requests {
+                for name in names {
+                    if !seen.insert(name.as_str()) {
+                        continue;
+                    }
+
+                    let key = (user_hash.clone(), name.clone());
+                    let fetch = fetch(transport.clone(), name.clone(), user.clone());
+                    let semaphore = semaphore.clone();
+                    tasks.spawn(async move {
+                        let result = match semaphore.acquire_owned().await {
+                            Ok(_permit) => fetch.await,
+                            Err(_) => Err(StatsigError::batch_processor("Fetch semaphore closed")),
+                        };
+                        if let Err(e) = &result {
+                            error!("Failed to fetch {} from API: {:?}", key.1, e);
+                        }
+                        (key, result)
+                    });
+                }
             }
         }
-    }
 
-    /// Fetches configs from the Statsig API
-    async fn fetch_configs_from_api(
-        transport: &StatsigTransport,
-        config_names: &[String],
-        user: &User,
-    ) -> Result<Vec<ConfigEvaluationResult>> {
-        let mut results = Vec::new();
-
-        for config_name in config_names {
-            let config_result = transport.get_config(config_name, user).await?;
-            results.push(config_result);
+        let mut fetched = HashMap::new();
+        while let Some(joined) = tasks.join_next().await {
+            match joined {
+                Ok((key, result)) => {
+                    fetched.insert(key, result);
+                }
+                Err(e) => error!("Fetch task failed: {:?}", e),
+            }
         }
 
-        Ok(results)
+        // Distribute results back to requesters
+        for (user_hash, group_requests) in user_groups {
+            for (names, _, response_tx) in group_requests {
+                let results: Vec<Result<T>> = names
+                    .into_iter()
+                    .map(|name| {
+                        fetched
+                            .get(&(user_hash.clone(), name))
+                            .cloned()
+                            .unwrap_or_else(|| Err(StatsigError::internal("Missing fetch result")))
+                    })
+                    .collect();
+                let _ = response_tx.send(results);
+            }
+        }
     }
 
     /// Hashes user for batch grouping
@@ -192,3 +328,187 @@ impl BatchProcessor {
         user.hash_for_cache()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        api::EvaluationReason,
+        events::{LogEventResponse, StatsigEvent},
+        specs::ConfigSpecs,
+    };
+    use serde_json::{Value, json};
+    use std::sync::Mutex;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    /// Serves configs whose value names the user they were fetched for,
+    /// recording each fetch and the peak number of concurrent fetches; every
+    /// other request fails
+    #[derive(Debug, Default)]
+    struct RecordingTransport {
+        fetches: Mutex<Vec<(String, String)>>,
+        in_flight: AtomicUsize,
+        max_in_flight: AtomicUsize,
+    }
+
+    #[async_trait::async_trait]
+    impl Transport for RecordingTransport {
+        async fn check_gates(
+            &self,
+            _gate_names: Vec<String>,
+            _user: &User,
+        ) -> Result<Vec<GateEvaluationResult>> {
+            Err(StatsigError::internal("Only get_config is recorded"))
+        }
+
+        async fn get_config(
+            &self,
+            config_name: &str,
+            user: &User,
+        ) -> Result<ConfigEvaluationResult> {
+            let user_id = user.user_id.clone().unwrap_or_default();
+            self.fetches
+                .lock()
+                .unwrap()
+                .push((user_id.clone(), config_name.to_string()));
+            let in_flight = self.in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+            self.max_in_flight.fetch_max(in_flight, Ordering::SeqCst);
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            self.in_flight.fetch_sub(1, Ordering::SeqCst);
+
+            if config_name == "broken" {
+                return Err(StatsigError::api(500, "broken config"));
+            }
+            Ok(ConfigEvaluationResult {
+                name: config_name.to_string(),
+                value: json!({ "user": user_id }),
+                rule_id: None,
+                group_name: None,
+                group: None,
+                secondary_exposures: Vec::new(),
+                reason: EvaluationReason::Network,
+            })
+        }
+
+        async fn get_layer(
+            &self,
+            _layer_name: &str,
+            _user: &User,
+        ) -> Result<LayerEvaluationResult> {
+            Err(StatsigError::internal("Only get_config is recorded"))
+        }
+
+        async fn download_config_specs(&self, _since_time: u64) -> Result<ConfigSpecs> {
+            Err(StatsigError::internal("Only get_config is recorded"))
+        }
+
+        async fn log_events(
+            &self,
+            _user: Option<&User>,
+            _events: &[StatsigEvent],
+        ) -> Result<LogEventResponse> {
+            Err(StatsigError::internal("Only get_config is recorded"))
+        }
+    }
+
+    fn user(user_id: &str) -> User {
+        User::builder().user_id(user_id).build().unwrap()
+    }
+
+    fn config_request(
+        names: &[&str],
+        user_id: &str,
+    ) -> (
+        BatchRequest,
+        oneshot::Receiver<Vec<Result<ConfigEvaluationResult>>>,
+    ) {
+        let (response_tx, response_rx) = oneshot::channel();
+        let request = BatchRequest::GetConfigs {
+            config_names: names.iter().map(|name| name.to_string()).collect(),
+            user: user(user_id),
+            response_tx,
+        };
+        (request, response_rx)
+    }
+
+    fn values(results: Vec<Result<ConfigEvaluationResult>>) -> Vec<(String, Value)> {
+        results
+            .into_iter()
+            .map(|result| {
+                let result = result.unwrap();
+                (result.name, result.value)
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn configs_are_fetched_once_per_user_and_routed_back() {
+        let recording = Arc::new(RecordingTransport::default());
+        let transport: Arc<dyn Transport> = recording.clone();
+        let (first, first_rx) = config_request(&["a", "b"], "u1");
+        let (second, second_rx) = config_request(&["b", "a", "b"], "u1");
+        let (third, third_rx) = config_request(&["a"], "u2");
+
+        let mut requests = vec![first, second, third];
+        BatchProcessor::process_config_batch(&transport, &mut requests, 4).await;
+
+        let mut fetches = recording.fetches.lock().unwrap().clone();
+        fetches.sort();
+        let expected: Vec<(String, String)> = [("u1", "a"), ("u1", "b"), ("u2", "a")]
+            .into_iter()
+            .map(|(user_id, name)| (user_id.to_string(), name.to_string()))
+            .collect();
+        assert_eq!(fetches, expected);
+
+        let u1 = json!({ "user": "u1" });
+        assert_eq!(
+            values(first_rx.await.unwrap()),
+            vec![("a".to_string(), u1.clone()), ("b".to_string(), u1.clone())]
+        );
+        assert_eq!(
+            values(second_rx.await.unwrap()),
+            vec![
+                ("b".to_string(), u1.clone()),
+                ("a".to_string(), u1.clone()),
+                ("b".to_string(), u1)
+            ]
+        );
+        assert_eq!(
+            values(third_rx.await.unwrap()),
+            vec![("a".to_string(), json!({ "user": "u2" }))]
+        );
+    }
+
+    #[tokio::test]
+    async fn config_fetches_respect_concurrency_limit() {
+        let recording = Arc::new(RecordingTransport::default());
+        let transport: Arc<dyn Transport> = recording.clone();
+        let (request, response_rx) = config_request(&["a", "b", "c", "d", "e", "f"], "u1");
+
+        let mut requests = vec![request];
+        BatchProcessor::process_config_batch(&transport, &mut requests, 2).await;
+
+        assert_eq!(response_rx.await.unwrap().len(), 6);
+        assert_eq!(recording.fetches.lock().unwrap().len(), 6);
+        assert_eq!(recording.max_in_flight.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn failed_config_only_reaches_its_requesters() {
+        let transport: Arc<dyn Transport> = Arc::new(RecordingTransport::default());
+        let (mixed, mixed_rx) = config_request(&["good", "broken"], "u1");
+        let (healthy, healthy_rx) = config_request(&["good"], "u1");
+
+        let mut requests = vec![mixed, healthy];
+        BatchProcessor::process_config_batch(&transport, &mut requests, 4).await;
+
+        let mixed = mixed_rx.await.unwrap();
+        assert_eq!(mixed[0].as_ref().unwrap().name, "good");
+        assert!(matches!(
+            mixed[1],
+            Err(StatsigError::Api { status: 500, .. })
+        ));
+        assert_eq!(values(healthy_rx.await.unwrap()).len(), 1);
+    }
+}
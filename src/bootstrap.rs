@@ -0,0 +1,94 @@
+//! Bootstrapping the client from serialized data
+//!
+//! A bootstrap string is either a config spec payload in the
+//! `/v1/download_config_specs` format, used by local evaluation, or a
+//! [`BootstrapSnapshot`] of precomputed evaluations for known users.
+
+use crate::{
+    api::{ConfigEvaluationResult, GateEvaluationResult, LayerEvaluationResult},
+    error::{Result, StatsigError},
+    specs::ConfigSpecs,
+    user::User,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Precomputed evaluations used to seed the cache at startup
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BootstrapSnapshot {
+    pub evaluations: Vec<UserEvaluations>,
+}
+
+/// Evaluations for a single user in a [`BootstrapSnapshot`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UserEvaluations {
+    pub user: User,
+    #[serde(default)]
+    pub gates: Vec<GateEvaluationResult>,
+    #[serde(default)]
+    pub configs: Vec<ConfigEvaluationResult>,
+    #[serde(default)]
+    pub layers: Vec<LayerEvaluationResult>,
+}
+
+/// Parsed bootstrap data
+#[derive(Debug)]
+pub(crate) enum Bootstrap {
+    Specs(ConfigSpecs),
+    Snapshot(BootstrapSnapshot),
+}
+
+impl Bootstrap {
+    /// Parses a bootstrap string, detecting its format from the top-level keys
+    pub fn parse(bootstrap: &str) -> Result<Self> {
+        let value: Value = serde_json::from_str(bootstrap)
+            .map_err(|e| StatsigError::configuration(format!("Invalid bootstrap JSON: {}", e)))?;
+
+        let has_key = |key: &str| value.get(key).is_some();
+        if has_key("feature_gates") || has_key("dynamic_configs") || has_key("layer_configs") {
+            serde_json::from_value(value).map(Self::Specs).map_err(|e| {
+                StatsigError::configuration(format!("Invalid bootstrap config specs: {}", e))
+            })
+        } else if has_key("evaluations") {
+            serde_json::from_value(value)
+                .map(Self::Snapshot)
+                .map_err(|e| {
+                    StatsigError::configuration(format!("Invalid bootstrap snapshot: {}", e))
+                })
+        } else {
+            Err(StatsigError::configuration(
+                "Bootstrap must contain config specs or an evaluation snapshot",
+            ))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_bootstrap_format() {
+        let specs = r#"{"feature_gates": [], "dynamic_configs": [], "time": 42}"#;
+        assert!(matches!(
+            Bootstrap::parse(specs),
+            Ok(Bootstrap::Specs(ConfigSpecs { time: 42, .. }))
+        ));
+
+        let snapshot = r#"{"evaluations": [{
+            "user": {"userID": "u1"},
+            "gates": [{"name": "g", "value": true, "rule_id": "r", "group_name": null}]
+        }]}"#;
+        match Bootstrap::parse(snapshot) {
+            Ok(Bootstrap::Snapshot(snapshot)) => {
+                assert_eq!(snapshot.evaluations[0].gates[0].name, "g");
+            }
+            other => panic!("unexpected bootstrap: {:?}", other),
+        }
+
+        assert!(matches!(
+            Bootstrap::parse("{}"),
+            Err(StatsigError::Configuration(_))
+        ));
+    }
+}
@@ -2,8 +2,20 @@
 //!
 //! This module provides metrics collection for cache performance monitoring.
 
+use crate::api::EntityType;
 use std::sync::atomic::{AtomicU64, Ordering};
 
+/// Why an entry left the cache
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EvictionCause {
+    /// The entry outlived its TTL
+    Expired,
+    /// The cache was over `cache_max_capacity`
+    Size,
+    /// The entry was invalidated through the client
+    Explicit,
+}
+
 /// Cache performance metrics
 #[derive(Debug, Default)]
 pub struct CacheMetrics {
@@ -13,8 +25,38 @@ pub struct CacheMetrics {
     misses: AtomicU64,
     /// Number of items inserted into cache
     inserts: AtomicU64,
-    /// Number of items evicted from cache
+    /// Number of items evicted, for any cause
     evictions: AtomicU64,
+    /// Number of items evicted because they expired
+    expired_evictions: AtomicU64,
+    /// Number of items evicted to stay within capacity
+    size_evictions: AtomicU64,
+    /// Number of items invalidated explicitly
+    explicit_evictions: AtomicU64,
+    gates: EntityCounters,
+    configs: EntityCounters,
+    layers: EntityCounters,
+}
+
+/// Hit and miss counters for one entity type
+#[derive(Debug, Default)]
+struct EntityCounters {
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl EntityCounters {
+    fn stats(&self) -> EntityCacheStats {
+        EntityCacheStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+        }
+    }
+
+    fn reset(&self) {
+        self.hits.store(0, Ordering::Relaxed);
+        self.misses.store(0, Ordering::Relaxed);
+    }
 }
 
 impl CacheMetrics {
@@ -33,6 +75,30 @@ impl CacheMetrics {
         self.misses.fetch_add(1, Ordering::Relaxed);
     }
 
+    /// Record a cache hit, also counting it for `entity_type`
+    pub fn record_entity_hit(&self, entity_type: &EntityType) {
+        self.record_hit();
+        self.counters(entity_type)
+            .hits
+            .fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Record a cache miss, also counting it for `entity_type`
+    pub fn record_entity_miss(&self, entity_type: &EntityType) {
+        self.record_miss();
+        self.counters(entity_type)
+            .misses
+            .fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn counters(&self, entity_type: &EntityType) -> &EntityCounters {
+        match entity_type {
+            EntityType::Gate => &self.gates,
+            EntityType::Config => &self.configs,
+            EntityType::Layer => &self.layers,
+        }
+    }
+
     /// Record a cache insertion
     pub fn record_insert(&self) {
         self.inserts.fetch_add(1, Ordering::Relaxed);
@@ -43,6 +109,17 @@ impl CacheMetrics {
         self.evictions.fetch_add(1, Ordering::Relaxed);
     }
 
+    /// Record a cache eviction, also counting it for `cause`
+    pub fn record_eviction_cause(&self, cause: EvictionCause) {
+        self.record_eviction();
+        let counter = match cause {
+            EvictionCause::Expired => &self.expired_evictions,
+            EvictionCause::Size => &self.size_evictions,
+            EvictionCause::Explicit => &self.explicit_evictions,
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+
     /// Get the total number of cache hits
     pub fn hits(&self) -> u64 {
         self.hits.load(Ordering::Relaxed)
@@ -84,38 +161,98 @@ impl CacheMetrics {
         self.misses.store(0, Ordering::Relaxed);
         self.inserts.store(0, Ordering::Relaxed);
         self.evictions.store(0, Ordering::Relaxed);
+        self.expired_evictions.store(0, Ordering::Relaxed);
+        self.size_evictions.store(0, Ordering::Relaxed);
+        self.explicit_evictions.store(0, Ordering::Relaxed);
+        self.gates.reset();
+        self.configs.reset();
+        self.layers.reset();
     }
 
     /// Get a summary of cache metrics
+    ///
+    /// The `entry_count` and `weighted_size` gauges are left at zero; they are
+    /// filled in by `StatsigClient::cache_metrics`, which owns the cache.
     pub fn summary(&self) -> CacheMetricsSummary {
         CacheMetricsSummary {
             hits: self.hits(),
             misses: self.misses(),
             inserts: self.inserts(),
             evictions: self.evictions(),
+            expired_evictions: self.expired_evictions.load(Ordering::Relaxed),
+            size_evictions: self.size_evictions.load(Ordering::Relaxed),
+            explicit_evictions: self.explicit_evictions.load(Ordering::Relaxed),
             total_requests: self.total_requests(),
             hit_ratio: self.hit_ratio(),
+            entry_count: 0,
+            weighted_size: 0,
+            gates: self.gates.stats(),
+            configs: self.configs.stats(),
+            layers: self.layers.stats(),
         }
     }
 }
 
+/// Hits and misses for one entity type
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct EntityCacheStats {
+    pub hits: u64,
+    pub misses: u64,
+}
+
 /// A summary of cache metrics for reporting
 #[derive(Debug, Clone)]
 pub struct CacheMetricsSummary {
     pub hits: u64,
     pub misses: u64,
     pub inserts: u64,
+    /// Total evictions, including any recorded without a cause
     pub evictions: u64,
+    pub expired_evictions: u64,
+    pub size_evictions: u64,
+    pub explicit_evictions: u64,
     pub total_requests: u64,
     pub hit_ratio: f64,
+    /// Approximate number of cached entries
+    pub entry_count: u64,
+    /// Approximate weighted size of the cache; equal to `entry_count` since
+    /// every entry has weight 1
+    pub weighted_size: u64,
+    /// Breakdown for feature gates
+    pub gates: EntityCacheStats,
+    /// Breakdown for dynamic configs and experiments
+    pub configs: EntityCacheStats,
+    /// Breakdown for layers
+    pub layers: EntityCacheStats,
 }
 
 impl std::fmt::Display for CacheMetricsSummary {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(
             f,
-            "Cache Metrics: {} hits, {} misses, {:.2}% hit ratio, {} inserts, {} evictions",
-            self.hits, self.misses, self.hit_ratio, self.inserts, self.evictions
+            "Cache Metrics: {} hits, {} misses, {:.2}% hit ratio, {} inserts, {} evictions, {} entries",
+            self.hits, self.misses, self.hit_ratio, self.inserts, self.evictions, self.entry_count
         )
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn per_type_and_per_cause_counts_roll_up_into_totals() {
+        let metrics = CacheMetrics::new();
+        metrics.record_hit();
+        metrics.record_entity_hit(&EntityType::Gate);
+        metrics.record_entity_miss(&EntityType::Layer);
+        metrics.record_eviction();
+        metrics.record_eviction_cause(EvictionCause::Size);
+
+        let summary = metrics.summary();
+        assert_eq!((summary.hits, summary.misses), (2, 1));
+        assert_eq!(summary.gates, EntityCacheStats { hits: 1, misses: 0 });
+        assert_eq!(summary.layers, EntityCacheStats { hits: 0, misses: 1 });
+        assert_eq!((summary.evictions, summary.size_evictions), (2, 1));
+    }
+}
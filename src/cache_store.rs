@@ -0,0 +1,542 @@
+//! Persistent and shared cache backends
+//!
+//! A [`CacheStore`] sits behind the client's in-memory cache. Every
+//! evaluation inserted into the in-memory cache is written through to the
+//! store, and the store is consulted in one of two ways:
+//!
+//! - Local stores such as [`FileCacheStore`] implement [`CacheStore::load`]
+//!   so a restarted client can warm-load its previous entries.
+//! - Shared stores such as Redis implement [`CacheStore::get`], which the
+//!   client reads through on every in-memory cache miss, so one replica's
+//!   fetch is reused by the others. [`MemoryCacheStore`] is a reference
+//!   implementation.
+//!
+//! # Key scheme
+//!
+//! Entries are keyed by `statsig:{type}:{name}:{user_hash}`, where `type` is
+//! `gate`, `config` or `layer`, `name` is the entity name and `user_hash` is
+//! the hex SHA-256 fingerprint from `User::hash_for_cache`. Values are
+//! [`StoredEvaluation`]s serialized as JSON; entries older than their TTL
+//! (`cache_ttl` unless the `cache_ttl_policy` says otherwise) are ignored, so
+//! stores may expire them after that long.
+//! Cache invalidations are forwarded as an [`EntrySelector`], whose
+//! [`key_pattern`](EntrySelector::key_pattern) is a glob over this scheme.
+
+use crate::{
+    api::{
+        ConfigEvaluationResult, EntityType, EvaluationReason, GateEvaluationResult,
+        LayerEvaluationResult,
+    },
+    error::{Result, StatsigError},
+};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, RwLock};
+use tracing::warn;
+
+/// Serialized evaluation result held by a [`CacheStore`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", content = "result", rename_all = "snake_case")]
+pub enum StoredResult {
+    Gate(GateEvaluationResult),
+    Config(ConfigEvaluationResult),
+    Layer(LayerEvaluationResult),
+}
+
+impl StoredResult {
+    /// Name of the gate, config or layer
+    pub fn name(&self) -> &str {
+        match self {
+            Self::Gate(result) => &result.name,
+            Self::Config(result) => &result.name,
+            Self::Layer(result) => &result.name,
+        }
+    }
+
+    pub fn entity_type(&self) -> EntityType {
+        match self {
+            Self::Gate(_) => EntityType::Gate,
+            Self::Config(_) => EntityType::Config,
+            Self::Layer(_) => EntityType::Layer,
+        }
+    }
+}
+
+/// A cached evaluation for one user, as persisted by a [`CacheStore`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StoredEvaluation {
+    /// Canonical user fingerprint from `User::hash_for_cache`
+    pub user_hash: String,
+    /// When the evaluation was fetched, in Unix milliseconds
+    pub stored_at_ms: i64,
+    pub result: StoredResult,
+    /// Where the evaluation came from, e.g. `Bootstrap`; entries written
+    /// before this was persisted load as `Network`
+    #[serde(default)]
+    pub reason: EvaluationReason,
+}
+
+impl StoredEvaluation {
+    /// Store key for the entry, following the module-level key scheme
+    pub fn key(&self) -> String {
+        entry_key(
+            &self.result.entity_type(),
+            self.result.name(),
+            &self.user_hash,
+        )
+    }
+}
+
+/// Selects entries to invalidate; `None` fields match every entry
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EntrySelector {
+    pub entity_type: Option<EntityType>,
+    pub name: Option<String>,
+    pub user_hash: Option<String>,
+}
+
+impl EntrySelector {
+    pub fn matches(&self, entry: &StoredEvaluation) -> bool {
+        self.matches_parts(
+            &entry.result.entity_type(),
+            entry.result.name(),
+            &entry.user_hash,
+        )
+    }
+
+    pub(crate) fn matches_parts(
+        &self,
+        entity_type: &EntityType,
+        name: &str,
+        user_hash: &str,
+    ) -> bool {
+        self.entity_type.as_ref().is_none_or(|t| t == entity_type)
+            && self.name.as_deref().is_none_or(|n| n == name)
+            && self.user_hash.as_deref().is_none_or(|h| h == user_hash)
+    }
+
+    /// Glob over the key scheme, e.g. `statsig:gate:*:{user_hash}`, for
+    /// stores that delete by pattern such as Redis `SCAN MATCH`
+    pub fn key_pattern(&self) -> String {
+        format!(
+            "statsig:{}:{}:{}",
+            self.entity_type
+                .as_ref()
+                .map_or("*", |entity_type| entity_type.as_str()),
+            self.name.as_deref().unwrap_or("*"),
+            self.user_hash.as_deref().unwrap_or("*")
+        )
+    }
+}
+
+/// Builds a store key: `statsig:{type}:{name}:{user_hash}`
+pub(crate) fn entry_key(entity_type: &EntityType, name: &str, user_hash: &str) -> String {
+    format!("statsig:{}:{}:{}", entity_type.as_str(), name, user_hash)
+}
+
+/// Storage backend for cached evaluations
+///
+/// Errors are logged by the client, which falls back to its in-memory cache
+/// and the Statsig API; they never fail an evaluation. Writes happen in the
+/// background, but lookups sit on the cache miss path.
+#[async_trait::async_trait]
+pub trait CacheStore: Debug + Send + Sync {
+    /// Loads entries to warm the in-memory cache; called once when the client starts
+    ///
+    /// Shared stores should keep the default, which loads nothing.
+    async fn load(&self) -> Result<Vec<StoredEvaluation>> {
+        Ok(Vec::new())
+    }
+
+    /// Looks up one entry by key on an in-memory cache miss
+    ///
+    /// Local stores should keep the default, which never finds anything.
+    async fn get(&self, _key: &str) -> Result<Option<StoredEvaluation>> {
+        Ok(None)
+    }
+
+    /// Persists an entry, replacing any entry with the same key
+    async fn store(&self, entry: StoredEvaluation) -> Result<()>;
+
+    /// Removes the entries matching `selector`; called when the client's
+    /// cache is invalidated
+    async fn invalidate(&self, selector: &EntrySelector) -> Result<()>;
+
+    /// Waits for buffered writes to be persisted; called when the client shuts down
+    async fn flush(&self) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// Shared in-memory [`CacheStore`]
+///
+/// Clones share the same entries, so clients built with clones of one store
+/// reuse each other's evaluations. Useful in tests and as a reference for
+/// adapters to external stores.
+#[derive(Debug, Clone, Default)]
+pub struct MemoryCacheStore {
+    entries: Arc<RwLock<HashMap<String, StoredEvaluation>>>,
+}
+
+impl MemoryCacheStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Number of stored entries
+    pub fn len(&self) -> usize {
+        self.entries.read().unwrap_or_else(|e| e.into_inner()).len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+#[async_trait::async_trait]
+impl CacheStore for MemoryCacheStore {
+    async fn get(&self, key: &str) -> Result<Option<StoredEvaluation>> {
+        let entries = self.entries.read().unwrap_or_else(|e| e.into_inner());
+        Ok(entries.get(key).cloned())
+    }
+
+    async fn store(&self, entry: StoredEvaluation) -> Result<()> {
+        let mut entries = self.entries.write().unwrap_or_else(|e| e.into_inner());
+        entries.insert(entry.key(), entry);
+        Ok(())
+    }
+
+    async fn invalidate(&self, selector: &EntrySelector) -> Result<()> {
+        let mut entries = self.entries.write().unwrap_or_else(|e| e.into_inner());
+        entries.retain(|_, entry| !selector.matches(entry));
+        Ok(())
+    }
+}
+
+/// [`CacheStore`] backed by a JSON lines file
+///
+/// Writes are buffered and appended by a background task, so storing an
+/// entry never waits on disk; call [`CacheStore::flush`] to wait for them.
+/// Invalidations append a marker line rather than rewriting the file. The
+/// file is compacted to the latest `max_entries` entries on load and, in the
+/// background, whenever it grows past twice that many lines. Lines that fail
+/// to parse are skipped, so a torn or corrupted file only loses the affected
+/// entries.
+#[derive(Debug, Clone)]
+pub struct FileCacheStore {
+    state: Arc<Mutex<FileState>>,
+    pending: Arc<Mutex<PendingWrites>>,
+}
+
+#[derive(Debug)]
+struct FileState {
+    path: PathBuf,
+    max_entries: usize,
+    /// Lines in the file; counted from the file on first use
+    lines: Option<usize>,
+}
+
+#[derive(Debug, Default)]
+struct PendingWrites {
+    lines: Vec<String>,
+    /// Whether a background task is appending pending lines
+    flushing: bool,
+}
+
+/// One line of the cache file
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(untagged)]
+enum FileLine {
+    Entry(StoredEvaluation),
+    /// Drops matching entries written before this line
+    Invalidation {
+        invalidate: EntrySelector,
+    },
+}
+
+impl FileCacheStore {
+    pub fn new(path: impl Into<PathBuf>, max_entries: usize) -> Self {
+        Self {
+            state: Arc::new(Mutex::new(FileState {
+                path: path.into(),
+                max_entries: max_entries.max(1),
+                lines: None,
+            })),
+            pending: Default::default(),
+        }
+    }
+
+    /// Runs blocking file I/O off the async runtime, after appending pending writes
+    async fn with_state<T, F>(&self, f: F) -> Result<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(&mut FileState) -> Result<T> + Send + 'static,
+    {
+        let state = self.state.clone();
+        let pending = self.pending.clone();
+        tokio::task::spawn_blocking(move || {
+            let mut state = state.lock().unwrap_or_else(|e| e.into_inner());
+            let lines =
+                std::mem::take(&mut pending.lock().unwrap_or_else(|e| e.into_inner()).lines);
+            state.append(&lines)?;
+            f(&mut state)
+        })
+        .await
+        .map_err(|e| StatsigError::cache(format!("Cache store task failed: {}", e)))?
+    }
+
+    /// Appends pending lines until none are left; runs on a blocking thread
+    fn flush_pending(&self) {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        loop {
+            let lines = {
+                let mut pending = self.pending.lock().unwrap_or_else(|e| e.into_inner());
+                if pending.lines.is_empty() {
+                    pending.flushing = false;
+                    return;
+                }
+                std::mem::take(&mut pending.lines)
+            };
+            if let Err(e) = state.append(&lines) {
+                warn!("Failed to write {} cache entries: {}", lines.len(), e);
+            }
+        }
+    }
+}
+
+impl FileState {
+    /// Appends lines, compacting the file once it has grown too large
+    fn append(&mut self, lines: &[String]) -> Result<()> {
+        if lines.is_empty() {
+            return Ok(());
+        }
+        let existing = match self.lines {
+            Some(count) => count,
+            None => count_lines(&self.path)?,
+        };
+
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .map_err(|e| io_error(&self.path, e))?;
+        let mut writer = BufWriter::new(file);
+        for line in lines {
+            writeln!(writer, "{}", line).map_err(|e| io_error(&self.path, e))?;
+        }
+        writer.flush().map_err(|e| io_error(&self.path, e))?;
+        self.lines = Some(existing + lines.len());
+
+        if existing + lines.len() > self.max_entries * 2 {
+            self.compact()?;
+        }
+        Ok(())
+    }
+
+    /// Rewrites the file with only the latest `max_entries` entries
+    fn compact(&mut self) -> Result<Vec<StoredEvaluation>> {
+        let entries = read_entries(&self.path, self.max_entries)?;
+        write_entries(&self.path, &entries)?;
+        self.lines = Some(entries.len());
+        Ok(entries)
+    }
+}
+
+fn encode_line(line: &FileLine) -> Result<String> {
+    serde_json::to_string(line)
+        .map_err(|e| StatsigError::serialization(format!("Failed to encode cache entry: {}", e)))
+}
+
+#[async_trait::async_trait]
+impl CacheStore for FileCacheStore {
+    async fn load(&self) -> Result<Vec<StoredEvaluation>> {
+        // Rewrite without corrupt lines and superseded entries
+        self.with_state(FileState::compact).await
+    }
+
+    async fn store(&self, entry: StoredEvaluation) -> Result<()> {
+        let line = encode_line(&FileLine::Entry(entry))?;
+        let mut pending = self.pending.lock().unwrap_or_else(|e| e.into_inner());
+        pending.lines.push(line);
+        if !pending.flushing {
+            pending.flushing = true;
+            let store = self.clone();
+            tokio::task::spawn_blocking(move || store.flush_pending());
+        }
+        Ok(())
+    }
+
+    async fn invalidate(&self, selector: &EntrySelector) -> Result<()> {
+        let line = encode_line(&FileLine::Invalidation {
+            invalidate: selector.clone(),
+        })?;
+        self.with_state(move |state| state.append(&[line])).await
+    }
+
+    async fn flush(&self) -> Result<()> {
+        self.with_state(|_| Ok(())).await
+    }
+}
+
+fn io_error(path: &Path, e: std::io::Error) -> StatsigError {
+    StatsigError::cache(format!("Cache file {}: {}", path.display(), e))
+}
+
+/// Counts the lines of the file, or 0 if it does not exist yet
+fn count_lines(path: &Path) -> Result<usize> {
+    match File::open(path) {
+        Ok(file) => Ok(BufReader::new(file).split(b'\n').count()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(0),
+        Err(e) => Err(io_error(path, e)),
+    }
+}
+
+/// Reads the latest entry per key, keeping the `max_entries` most recent
+fn read_entries(path: &Path, max_entries: usize) -> Result<Vec<StoredEvaluation>> {
+    let file = match File::open(path) {
+        Ok(file) => file,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(io_error(path, e)),
+    };
+
+    let mut latest: HashMap<String, StoredEvaluation> = HashMap::new();
+    let mut corrupt = 0;
+    for line in BufReader::new(file).lines() {
+        let Ok(line) = line else {
+            corrupt += 1;
+            continue;
+        };
+        if line.trim().is_empty() {
+            continue;
+        }
+        match serde_json::from_str::<FileLine>(&line) {
+            Ok(FileLine::Entry(entry)) => {
+                let key = entry.key();
+                if latest
+                    .get(&key)
+                    .is_none_or(|existing| existing.stored_at_ms <= entry.stored_at_ms)
+                {
+                    latest.insert(key, entry);
+                }
+            }
+            Ok(FileLine::Invalidation { invalidate }) => {
+                latest.retain(|_, entry| !invalidate.matches(entry));
+            }
+            Err(_) => corrupt += 1,
+        }
+    }
+    if corrupt > 0 {
+        warn!(
+            "Skipped {} corrupt entries in cache file {}",
+            corrupt,
+            path.display()
+        );
+    }
+
+    let mut entries: Vec<_> = latest.into_values().collect();
+    entries.sort_by_key(|entry| std::cmp::Reverse(entry.stored_at_ms));
+    entries.truncate(max_entries);
+    Ok(entries)
+}
+
+/// Atomically replaces the file with `entries`
+fn write_entries(path: &Path, entries: &[StoredEvaluation]) -> Result<()> {
+    let tmp_path = path.with_extension("tmp");
+    let file = File::create(&tmp_path).map_err(|e| io_error(&tmp_path, e))?;
+    let mut writer = BufWriter::new(file);
+    for entry in entries {
+        let line = serde_json::to_string(entry).map_err(|e| {
+            StatsigError::serialization(format!("Failed to encode cache entry: {}", e))
+        })?;
+        writeln!(writer, "{}", line).map_err(|e| io_error(&tmp_path, e))?;
+    }
+    writer.flush().map_err(|e| io_error(&tmp_path, e))?;
+    std::fs::rename(&tmp_path, path).map_err(|e| io_error(path, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn gate_entry(user_hash: &str, value: bool, stored_at_ms: i64) -> StoredEvaluation {
+        StoredEvaluation {
+            user_hash: user_hash.to_string(),
+            stored_at_ms,
+            result: StoredResult::Gate(GateEvaluationResult {
+                name: "gate".to_string(),
+                value,
+                rule_id: None,
+                group_name: None,
+                secondary_exposures: Vec::new(),
+                reason: EvaluationReason::Network,
+            }),
+            reason: EvaluationReason::Network,
+        }
+    }
+
+    #[tokio::test]
+    async fn loads_latest_entries_and_skips_corrupt_lines() {
+        let dir = std::env::temp_dir().join(format!("statsig-cache-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("cache.jsonl");
+
+        let store = FileCacheStore::new(&path, 2);
+        store.store(gate_entry("u1", false, 1)).await.unwrap();
+        store.store(gate_entry("u1", true, 2)).await.unwrap();
+        store.store(gate_entry("u2", true, 3)).await.unwrap();
+        store.store(gate_entry("u3", true, 4)).await.unwrap();
+        store.flush().await.unwrap();
+        let mut file = OpenOptions::new().append(true).open(&path).unwrap();
+        writeln!(file, "{{\"user_hash\": \"u4\", \"stored_at").unwrap();
+
+        let entries = FileCacheStore::new(&path, 2).load().await.unwrap();
+        let mut users: Vec<_> = entries.iter().map(|e| e.user_hash.as_str()).collect();
+        users.sort();
+        assert_eq!(users, ["u2", "u3"]);
+
+        let entries = FileCacheStore::new(&path, 10).load().await.unwrap();
+        assert_eq!(entries.len(), 2);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn invalidation_and_compaction_survive_restart() {
+        let dir = std::env::temp_dir().join(format!("statsig-cache-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("cache.jsonl");
+
+        let store = FileCacheStore::new(&path, 2);
+        store.store(gate_entry("u1", true, 1)).await.unwrap();
+        store.store(gate_entry("u2", true, 2)).await.unwrap();
+        store.flush().await.unwrap();
+
+        // A new handle counts the existing lines, so it compacts on time
+        let store = FileCacheStore::new(&path, 2);
+        store
+            .invalidate(&EntrySelector {
+                user_hash: Some("u1".to_string()),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        store.store(gate_entry("u3", true, 3)).await.unwrap();
+        store.store(gate_entry("u1", false, 4)).await.unwrap();
+        store.flush().await.unwrap();
+        assert_eq!(count_lines(&path).unwrap(), 2);
+
+        let entries = FileCacheStore::new(&path, 10).load().await.unwrap();
+        let mut users: Vec<_> = entries
+            .iter()
+            .map(|e| (e.user_hash.as_str(), e.stored_at_ms))
+            .collect();
+        users.sort();
+        assert_eq!(users, [("u1", 4), ("u3", 3)]);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
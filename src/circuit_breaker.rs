@@ -0,0 +1,348 @@
+//! Circuit breaker for requests to the Statsig API
+//!
+//! When the API keeps failing, the breaker opens and requests fail
+//! immediately with `StatsigError::CircuitOpen` instead of waiting on
+//! timeouts and retries, which lets `offline_fallback` serve fallbacks
+//! right away. After `open_duration` the breaker half-opens and lets a few
+//! probe requests through; if they succeed it closes again.
+//!
+//! Only gate, config and layer evaluation requests go through the breaker.
+//! Event delivery and config spec downloads neither count towards it nor
+//! fail fast while it is open.
+
+use crate::error::{Result, StatsigError, local_error};
+use bon::Builder;
+use http::Extensions;
+use reqwest::{Request, Response};
+use reqwest_middleware::{Middleware, Next};
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+use tracing::{info, warn};
+
+/// When the circuit breaker opens and how it recovers
+#[derive(Debug, Clone, Builder)]
+pub struct CircuitBreakerConfig {
+    /// Consecutive failed requests that open the breaker
+    #[builder(default = 5)]
+    pub consecutive_failures: u32,
+    /// Failure rate over the last `window_size` requests that opens the breaker
+    #[builder(default = 0.5)]
+    pub failure_rate_threshold: f64,
+    /// Number of recent requests the failure rate is computed over; the rate
+    /// is only checked once that many requests have completed
+    #[builder(default = 20)]
+    pub window_size: usize,
+    /// How long the breaker stays open before probing the API
+    #[builder(default = Duration::from_secs(30))]
+    pub open_duration: Duration,
+    /// Probe requests let through while half-open; all must succeed to close
+    #[builder(default = 1)]
+    pub half_open_probes: u32,
+}
+
+impl Default for CircuitBreakerConfig {
+    fn default() -> Self {
+        Self::builder().build()
+    }
+}
+
+impl CircuitBreakerConfig {
+    pub fn validate(&self) -> Result<()> {
+        if self.consecutive_failures == 0 {
+            return Err(StatsigError::configuration(
+                "Circuit breaker consecutive failures must be greater than 0",
+            ));
+        }
+
+        if !(self.failure_rate_threshold > 0.0 && self.failure_rate_threshold <= 1.0) {
+            return Err(StatsigError::configuration(
+                "Circuit breaker failure rate threshold must be in (0, 1]",
+            ));
+        }
+
+        if self.window_size == 0 {
+            return Err(StatsigError::configuration(
+                "Circuit breaker window size must be greater than 0",
+            ));
+        }
+
+        if self.open_duration.is_zero() {
+            return Err(StatsigError::configuration(
+                "Circuit breaker open duration must be greater than 0",
+            ));
+        }
+
+        if self.half_open_probes == 0 {
+            return Err(StatsigError::configuration(
+                "Circuit breaker half-open probes must be greater than 0",
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+/// State of the circuit breaker
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CircuitState {
+    /// Requests flow normally
+    Closed,
+    /// Requests fail fast without reaching the API
+    Open,
+    /// Probe requests are testing whether the API has recovered
+    HalfOpen,
+}
+
+#[derive(Debug)]
+pub(crate) struct CircuitBreaker {
+    config: CircuitBreakerConfig,
+    inner: Mutex<BreakerState>,
+}
+
+#[derive(Debug)]
+struct BreakerState {
+    state: CircuitState,
+    consecutive_failures: u32,
+    /// Recent outcomes while closed; `true` for a failure
+    outcomes: VecDeque<bool>,
+    opened_at: Instant,
+    probes_in_flight: u32,
+    probe_successes: u32,
+}
+
+impl BreakerState {
+    fn open(&mut self) {
+        self.state = CircuitState::Open;
+        self.opened_at = Instant::now();
+        self.consecutive_failures = 0;
+        self.outcomes.clear();
+    }
+
+    fn close(&mut self) {
+        self.state = CircuitState::Closed;
+        self.consecutive_failures = 0;
+        self.outcomes.clear();
+    }
+}
+
+/// Admission for one request; records the outcome
+pub(crate) struct Permit<'a> {
+    breaker: &'a CircuitBreaker,
+    probe: bool,
+    recorded: bool,
+}
+
+impl Permit<'_> {
+    pub fn record(mut self, success: bool) {
+        self.recorded = true;
+        self.breaker.record(self.probe, success);
+    }
+}
+
+impl Drop for Permit<'_> {
+    /// Frees the probe slot if the request was cancelled before completing
+    fn drop(&mut self) {
+        if self.probe && !self.recorded {
+            let mut inner = self.breaker.lock();
+            if inner.state == CircuitState::HalfOpen {
+                inner.probes_in_flight = inner.probes_in_flight.saturating_sub(1);
+            }
+        }
+    }
+}
+
+impl CircuitBreaker {
+    pub fn new(config: CircuitBreakerConfig) -> Self {
+        Self {
+            inner: Mutex::new(BreakerState {
+                state: CircuitState::Closed,
+                consecutive_failures: 0,
+                outcomes: VecDeque::with_capacity(config.window_size),
+                opened_at: Instant::now(),
+                probes_in_flight: 0,
+                probe_successes: 0,
+            }),
+            config,
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, BreakerState> {
+        self.inner.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    pub fn state(&self) -> CircuitState {
+        let inner = self.lock();
+        match inner.state {
+            CircuitState::Open if inner.opened_at.elapsed() >= self.config.open_duration => {
+                CircuitState::HalfOpen
+            }
+            state => state,
+        }
+    }
+
+    /// Admits a request, or returns how long until the breaker will probe again
+    pub fn try_acquire(&self) -> std::result::Result<Permit<'_>, Duration> {
+        let mut inner = self.lock();
+        if inner.state == CircuitState::Open {
+            let elapsed = inner.opened_at.elapsed();
+            if elapsed < self.config.open_duration {
+                return Err(self.config.open_duration - elapsed);
+            }
+            inner.state = CircuitState::HalfOpen;
+            inner.probes_in_flight = 0;
+            inner.probe_successes = 0;
+            info!("Statsig API circuit breaker half-open, sending probe requests");
+        }
+
+        let probe = inner.state == CircuitState::HalfOpen;
+        if probe {
+            if inner.probes_in_flight >= self.config.half_open_probes {
+                return Err(Duration::ZERO);
+            }
+            inner.probes_in_flight += 1;
+        }
+        Ok(Permit {
+            breaker: self,
+            probe,
+            recorded: false,
+        })
+    }
+
+    fn record(&self, probe: bool, success: bool) {
+        let mut inner = self.lock();
+        match inner.state {
+            CircuitState::HalfOpen if probe => {
+                inner.probes_in_flight = inner.probes_in_flight.saturating_sub(1);
+                if !success {
+                    inner.open();
+                    warn!("Statsig API circuit breaker probe failed, reopening");
+                    return;
+                }
+                inner.probe_successes += 1;
+                if inner.probe_successes >= self.config.half_open_probes {
+                    inner.close();
+                    info!("Statsig API circuit breaker closed, API recovered");
+                }
+            }
+            CircuitState::Closed => {
+                if success {
+                    inner.consecutive_failures = 0;
+                } else {
+                    inner.consecutive_failures += 1;
+                }
+                if inner.outcomes.len() == self.config.window_size {
+                    inner.outcomes.pop_front();
+                }
+                inner.outcomes.push_back(!success);
+
+                let failures = inner.outcomes.iter().filter(|failed| **failed).count();
+                let window_full = inner.outcomes.len() == self.config.window_size;
+                let failure_rate = failures as f64 / inner.outcomes.len() as f64;
+                if inner.consecutive_failures >= self.config.consecutive_failures
+                    || (window_full && failure_rate >= self.config.failure_rate_threshold)
+                {
+                    inner.open();
+                    warn!(
+                        "Statsig API circuit breaker opened after repeated failures; failing fast for {:?}",
+                        self.config.open_duration
+                    );
+                }
+            }
+            // Late results from requests admitted before the state changed
+            _ => {}
+        }
+    }
+}
+
+/// Outermost middleware, so a request and all its retries count as one outcome
+#[derive(Debug, Clone)]
+pub(crate) struct CircuitBreakerMiddleware {
+    breaker: Arc<CircuitBreaker>,
+}
+
+impl CircuitBreakerMiddleware {
+    pub fn new(breaker: Arc<CircuitBreaker>) -> Self {
+        Self { breaker }
+    }
+}
+
+#[async_trait::async_trait]
+impl Middleware for CircuitBreakerMiddleware {
+    async fn handle(
+        &self,
+        req: Request,
+        extensions: &mut Extensions,
+        next: Next<'_>,
+    ) -> reqwest_middleware::Result<Response> {
+        let permit = self.breaker.try_acquire().map_err(|retry_after| {
+            reqwest_middleware::Error::middleware(StatsigError::circuit_open(retry_after))
+        })?;
+
+        let result = next.run(req, extensions).await;
+        let success = match &result {
+            Ok(response) => {
+                let status = response.status();
+                !(status.is_server_error() || status == reqwest::StatusCode::REQUEST_TIMEOUT)
+            }
+            // Requests rejected locally, e.g. by the rate limiter, never
+            // reached the API and say nothing about its health
+            Err(e) if local_error(e).is_some() => return result,
+            Err(_) => false,
+        };
+        permit.record(success);
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn breaker(open_duration: Duration) -> CircuitBreaker {
+        CircuitBreaker::new(
+            CircuitBreakerConfig::builder()
+                .consecutive_failures(3)
+                .window_size(4)
+                .failure_rate_threshold(0.75)
+                .open_duration(open_duration)
+                .build(),
+        )
+    }
+
+    #[test]
+    fn opens_after_consecutive_failures_and_recovers_through_probe() {
+        let breaker = breaker(Duration::from_millis(20));
+        for _ in 0..3 {
+            breaker.try_acquire().unwrap().record(false);
+        }
+        assert_eq!(breaker.state(), CircuitState::Open);
+        assert!(breaker.try_acquire().is_err());
+
+        std::thread::sleep(Duration::from_millis(30));
+        let probe = breaker.try_acquire().unwrap();
+        assert_eq!(breaker.state(), CircuitState::HalfOpen);
+        // Only one probe at a time
+        assert!(breaker.try_acquire().is_err());
+        probe.record(true);
+        assert_eq!(breaker.state(), CircuitState::Closed);
+    }
+
+    #[test]
+    fn opens_on_failure_rate_and_reopens_on_failed_probe() {
+        let breaker = breaker(Duration::from_millis(20));
+        for success in [false, true, false, false] {
+            breaker.try_acquire().unwrap().record(success);
+        }
+        assert_eq!(breaker.state(), CircuitState::Open);
+
+        std::thread::sleep(Duration::from_millis(30));
+        breaker.try_acquire().unwrap().record(false);
+        assert!(breaker.try_acquire().is_err());
+
+        // A cancelled probe frees its slot
+        std::thread::sleep(Duration::from_millis(30));
+        drop(breaker.try_acquire().unwrap());
+        assert!(breaker.try_acquire().is_ok());
+    }
+}
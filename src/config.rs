@@ -1,10 +1,17 @@
+use crate::api::EntityType;
+use crate::cache_store::CacheStore;
+use crate::circuit_breaker::CircuitBreakerConfig;
+use crate::rate_limit::RateLimitConfig;
 use bon::Builder;
 use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use std::sync::Arc;
 use std::time::Duration;
 
 #[derive(Debug, Clone, Builder)]
 pub struct StatsigClientConfig {
-    #[builder(into)]
+    #[builder(into, default)]
     pub api_key: String,
     #[builder(default = "https://api.statsig.com".to_string())]
     pub base_url: String,
@@ -16,18 +23,62 @@ pub struct StatsigClientConfig {
     pub retry_attempts: u32,
     #[builder(default = Duration::from_millis(1000))]
     pub retry_delay: Duration,
+    /// Fails evaluation requests fast while the Statsig API keeps failing; off by default
+    pub circuit_breaker: Option<CircuitBreakerConfig>,
+    /// Caps the outbound request rate and concurrency; unlimited by default
+    pub rate_limit: Option<RateLimitConfig>,
     #[builder(default = Duration::from_secs(300))]
     pub cache_ttl: Duration,
+    pub cache_soft_ttl: Option<Duration>,
+    /// Per-type and per-entity TTLs; entities without a rule use `cache_ttl`
+    #[builder(default)]
+    pub cache_ttl_policy: CacheTtlPolicy,
     #[builder(default = 10000)]
     pub cache_max_capacity: u64,
+    /// Persists cached evaluations so they survive restarts
+    pub cache_store: Option<Arc<dyn CacheStore>>,
+    /// Longest a shared cache store lookup may take before the evaluation is
+    /// fetched from the Statsig API instead
+    #[builder(default = Duration::from_millis(100))]
+    pub cache_store_timeout: Duration,
     #[builder(default = 10)]
     pub batch_size: usize,
     #[builder(default = Duration::from_millis(100))]
     pub batch_flush_interval: Duration,
+    #[builder(default = 8)]
+    pub config_fetch_concurrency: usize,
+    #[builder(default = 10_000)]
+    pub event_queue_capacity: usize,
+    #[builder(default = 500)]
+    pub event_flush_size: usize,
+    #[builder(default = Duration::from_secs(10))]
+    pub event_flush_interval: Duration,
+    #[builder(default = 3)]
+    pub event_max_flush_retries: u32,
+    #[builder(default)]
+    pub event_drop_policy: EventDropPolicy,
+    #[builder(default = Duration::from_secs(5))]
+    pub shutdown_timeout: Duration,
     #[builder(default = false)]
     pub offline_fallback: bool,
     #[builder(default = false)]
+    pub offline_default_gate_value: bool,
+    #[builder(default)]
+    pub offline_default_config_value: serde_json::Value,
+    #[builder(default = false)]
     pub exposure_logging_disabled: bool,
+    #[builder(default = Duration::from_secs(60))]
+    pub exposure_dedupe_window: Duration,
+    #[builder(default = false)]
+    pub local_evaluation: bool,
+    #[builder(default = Duration::from_secs(10))]
+    pub config_sync_interval: Duration,
+    #[builder(into)]
+    pub overrides_file: Option<PathBuf>,
+    #[builder(default = Duration::from_secs(1))]
+    pub overrides_poll_interval: Duration,
+    #[builder(default = false)]
+    pub local_mode: bool,
     #[builder(default = "rust-client".to_string())]
     pub sdk_type: String,
     #[builder(default = env!("CARGO_PKG_VERSION").to_string())]
@@ -48,7 +99,8 @@ impl StatsigClientConfig {
     }
 
     pub fn validate(&self) -> crate::error::Result<()> {
-        if self.api_key.is_empty() {
+        // Local mode never talks to Statsig, so it does not need a key
+        if self.api_key.is_empty() && !self.local_mode {
             return Err(crate::error::StatsigError::configuration(
                 "API key cannot be empty",
             ));
@@ -72,20 +124,188 @@ impl StatsigClientConfig {
             ));
         }
 
+        if let Some(circuit_breaker) = &self.circuit_breaker {
+            circuit_breaker.validate()?;
+        }
+
+        if let Some(rate_limit) = &self.rate_limit {
+            rate_limit.validate()?;
+        }
+
         if self.batch_size == 0 {
             return Err(crate::error::StatsigError::configuration(
                 "Batch size must be greater than 0",
             ));
         }
 
+        if self.event_queue_capacity == 0 {
+            return Err(crate::error::StatsigError::configuration(
+                "Event queue capacity must be greater than 0",
+            ));
+        }
+
+        if self.event_flush_size == 0 {
+            return Err(crate::error::StatsigError::configuration(
+                "Event flush size must be greater than 0",
+            ));
+        }
+
+        if self.event_flush_interval.is_zero() {
+            return Err(crate::error::StatsigError::configuration(
+                "Event flush interval must be greater than 0",
+            ));
+        }
+
+        if self.config_fetch_concurrency == 0 {
+            return Err(crate::error::StatsigError::configuration(
+                "Config fetch concurrency must be greater than 0",
+            ));
+        }
+
         if self.cache_ttl.as_secs() == 0 {
             return Err(crate::error::StatsigError::configuration(
                 "Cache TTL must be greater than 0",
             ));
         }
 
+        if let Some(soft_ttl) = self.cache_soft_ttl {
+            if soft_ttl.is_zero() || soft_ttl >= self.cache_ttl {
+                return Err(crate::error::StatsigError::configuration(
+                    "Cache soft TTL must be greater than 0 and less than the cache TTL",
+                ));
+            }
+        }
+
+        if self.cache_store.is_some() && self.cache_store_timeout.is_zero() {
+            return Err(crate::error::StatsigError::configuration(
+                "Cache store timeout must be greater than 0",
+            ));
+        }
+
+        if self.cache_ttl_policy.has_zero_ttl() {
+            return Err(crate::error::StatsigError::configuration(
+                "Cache TTL policy durations must be greater than 0; use never_cache to disable caching",
+            ));
+        }
+
+        if self.overrides_file.is_some() && self.overrides_poll_interval.is_zero() {
+            return Err(crate::error::StatsigError::configuration(
+                "Overrides poll interval must be greater than 0",
+            ));
+        }
+
+        if self.local_evaluation && self.config_sync_interval.is_zero() {
+            return Err(crate::error::StatsigError::configuration(
+                "Config sync interval must be greater than 0",
+            ));
+        }
+
         Ok(())
     }
+
+    /// TTL for an entity, or `None` if it must not be cached
+    pub(crate) fn cache_ttl_for(&self, entity_type: &EntityType, name: &str) -> Option<Duration> {
+        self.cache_ttl_policy
+            .ttl_for(entity_type, name, self.cache_ttl)
+    }
+}
+
+/// Cache TTLs by entity type and name
+///
+/// The most specific rule wins: never-cache, then an exact name, then the
+/// longest matching name prefix, then the entity type. Name rules apply to
+/// gates, configs and layers alike.
+///
+/// ```
+/// use statsig_client::{CacheTtlPolicy, EntityType};
+/// use std::time::Duration;
+///
+/// let policy = CacheTtlPolicy::new()
+///     .entity_type_ttl(EntityType::Config, Duration::from_secs(3600))
+///     .prefix_ttl("kill_switch_", Duration::from_secs(5))
+///     .never_cache("checkout_rollout");
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct CacheTtlPolicy {
+    entity_types: HashMap<EntityType, Duration>,
+    names: HashMap<String, Duration>,
+    prefixes: Vec<(String, Duration)>,
+    never_cache: HashSet<String>,
+}
+
+impl CacheTtlPolicy {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the TTL for every entity of `entity_type`
+    pub fn entity_type_ttl(mut self, entity_type: EntityType, ttl: Duration) -> Self {
+        self.entity_types.insert(entity_type, ttl);
+        self
+    }
+
+    /// Sets the TTL for entities named exactly `name`
+    pub fn name_ttl(mut self, name: impl Into<String>, ttl: Duration) -> Self {
+        self.names.insert(name.into(), ttl);
+        self
+    }
+
+    /// Sets the TTL for entities whose name starts with `prefix`
+    pub fn prefix_ttl(mut self, prefix: impl Into<String>, ttl: Duration) -> Self {
+        self.prefixes.push((prefix.into(), ttl));
+        self
+    }
+
+    /// Never caches entities named `name`; every evaluation is fetched
+    pub fn never_cache(mut self, name: impl Into<String>) -> Self {
+        self.never_cache.insert(name.into());
+        self
+    }
+
+    /// TTL for an entity, `default_ttl` if no rule matches, or `None` if it
+    /// must not be cached
+    pub(crate) fn ttl_for(
+        &self,
+        entity_type: &EntityType,
+        name: &str,
+        default_ttl: Duration,
+    ) -> Option<Duration> {
+        if self.never_cache.contains(name) {
+            return None;
+        }
+        if let Some(ttl) = self.names.get(name) {
+            return Some(*ttl);
+        }
+        let prefix_ttl = self
+            .prefixes
+            .iter()
+            .filter(|(prefix, _)| name.starts_with(prefix.as_str()))
+            .max_by_key(|(prefix, _)| prefix.len())
+            .map(|(_, ttl)| *ttl);
+        Some(
+            prefix_ttl
+                .or_else(|| self.entity_types.get(entity_type).copied())
+                .unwrap_or(default_ttl),
+        )
+    }
+
+    fn has_zero_ttl(&self) -> bool {
+        self.entity_types
+            .values()
+            .chain(self.names.values())
+            .chain(self.prefixes.iter().map(|(_, ttl)| ttl))
+            .any(Duration::is_zero)
+    }
+}
+
+/// What to do with new events when the event queue is full
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum EventDropPolicy {
+    /// Discard the new event and log a warning
+    #[default]
+    DropNewest,
+    /// Return `StatsigError::EventQueueFull` to the caller
+    Reject,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -106,3 +326,63 @@ impl Default for ClientInfo {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn most_specific_ttl_rule_wins() {
+        let config = StatsigClientConfig::builder()
+            .api_key("test_key")
+            .cache_ttl(Duration::from_secs(300))
+            .cache_ttl_policy(
+                CacheTtlPolicy::new()
+                    .entity_type_ttl(EntityType::Config, Duration::from_secs(3600))
+                    .prefix_ttl("kill_", Duration::from_secs(10))
+                    .prefix_ttl("kill_switch_", Duration::from_secs(5))
+                    .name_ttl("kill_switch_checkout", Duration::from_secs(1))
+                    .never_cache("kill_switch_payments"),
+            )
+            .build();
+
+        let ttl = |entity_type, name| config.cache_ttl_for(&entity_type, name);
+        assert_eq!(
+            ttl(EntityType::Gate, "new_checkout"),
+            Some(Duration::from_secs(300))
+        );
+        assert_eq!(
+            ttl(EntityType::Config, "ui_settings"),
+            Some(Duration::from_secs(3600))
+        );
+        assert_eq!(
+            ttl(EntityType::Gate, "kill_all"),
+            Some(Duration::from_secs(10))
+        );
+        assert_eq!(
+            ttl(EntityType::Config, "kill_switch_search"),
+            Some(Duration::from_secs(5))
+        );
+        assert_eq!(
+            ttl(EntityType::Gate, "kill_switch_checkout"),
+            Some(Duration::from_secs(1))
+        );
+        assert_eq!(ttl(EntityType::Gate, "kill_switch_payments"), None);
+    }
+
+    #[test]
+    fn soft_ttl_may_exceed_short_policy_ttls() {
+        let config = StatsigClientConfig::builder()
+            .api_key("test_key")
+            .cache_ttl(Duration::from_secs(300))
+            .cache_soft_ttl(Duration::from_secs(60))
+            .cache_ttl_policy(
+                CacheTtlPolicy::new()
+                    .entity_type_ttl(EntityType::Config, Duration::from_secs(3600))
+                    .prefix_ttl("kill_switch_", Duration::from_secs(5)),
+            )
+            .build();
+
+        assert!(config.validate().is_ok());
+    }
+}
@@ -0,0 +1,118 @@
+//! Typed access to dynamic config values
+
+use crate::{
+    api::{ConfigEvaluationResult, EvaluationReason},
+    error::{Result, StatsigError},
+    experiments::get_parameter,
+};
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+
+/// A dynamic config evaluation for a user
+#[derive(Debug, Clone)]
+pub struct DynamicConfig {
+    pub name: String,
+    pub value: Value,
+    pub rule_id: Option<String>,
+    pub group_name: Option<String>,
+    pub reason: EvaluationReason,
+}
+
+impl DynamicConfig {
+    /// Reads a parameter, returning `default` if it is missing or has a different type
+    pub fn get<T: DeserializeOwned>(&self, key: &str, default: T) -> T {
+        get_parameter(&self.value, key).unwrap_or(default)
+    }
+
+    /// Reads a parameter, failing if it exists but does not match `T`
+    pub fn try_get<T: DeserializeOwned>(&self, key: &str) -> Result<Option<T>> {
+        self.value
+            .get(key)
+            .map(|value| deserialize_config(&self.name, Some(key), value))
+            .transpose()
+    }
+
+    /// Deserializes the whole config value into `T`
+    pub fn deserialize<T: DeserializeOwned>(&self) -> Result<T> {
+        deserialize_config(&self.name, None, &self.value)
+    }
+}
+
+impl From<ConfigEvaluationResult> for DynamicConfig {
+    fn from(result: ConfigEvaluationResult) -> Self {
+        Self {
+            name: result.name,
+            value: result.value,
+            rule_id: result.rule_id,
+            group_name: result.group_name,
+            reason: result.reason,
+        }
+    }
+}
+
+/// Deserializes a config value, reporting the JSON path that failed to match
+pub(crate) fn deserialize_config<T: DeserializeOwned>(
+    config_name: &str,
+    key: Option<&str>,
+    value: &Value,
+) -> Result<T> {
+    serde_path_to_error::deserialize(value).map_err(|e| {
+        let inner_path = e.path().to_string();
+        let path = match key {
+            Some(key) if inner_path == "." => key.to_string(),
+            Some(key) => format!("{}.{}", key, inner_path),
+            None => inner_path,
+        };
+        StatsigError::config_deserialization(config_name, path, e.into_inner().to_string())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+
+    #[derive(Debug, Deserialize)]
+    #[allow(dead_code)]
+    struct Limits {
+        max_items: u32,
+    }
+
+    #[derive(Debug, Deserialize)]
+    #[allow(dead_code)]
+    struct Settings {
+        theme: String,
+        limits: Limits,
+    }
+
+    #[test]
+    fn mismatches_report_json_path() {
+        let config = DynamicConfig {
+            name: "ui_settings".to_string(),
+            value: serde_json::json!({"theme": "dark", "limits": {"max_items": "ten"}}),
+            rule_id: None,
+            group_name: None,
+            reason: EvaluationReason::Network,
+        };
+
+        assert_eq!(config.get("theme", "light".to_string()), "dark");
+        assert_eq!(config.get("missing", 3), 3);
+
+        match config.deserialize::<Settings>() {
+            Err(StatsigError::ConfigDeserialization {
+                config_name, path, ..
+            }) => {
+                assert_eq!(config_name, "ui_settings");
+                assert_eq!(path, "limits.max_items");
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        match config.try_get::<Limits>("limits") {
+            Err(StatsigError::ConfigDeserialization { path, .. }) => {
+                assert_eq!(path, "limits.max_items")
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+}
@@ -25,12 +25,21 @@ pub enum StatsigError {
     #[error("Batch processor error: {0}")]
     BatchProcessor(String),
 
+    #[error("Event queue full: capacity {capacity} reached")]
+    EventQueueFull { capacity: usize },
+
     #[error("Rate limited: retry after {retry_after_seconds} seconds")]
     RateLimited { retry_after_seconds: u64 },
 
+    #[error("Circuit breaker open: Statsig API unavailable, retry after {retry_after_ms} ms")]
+    CircuitOpen { retry_after_ms: u64 },
+
     #[error("Unauthorized: invalid API key")]
     Unauthorized,
 
+    #[error("Client has been shut down")]
+    ShutDown,
+
     #[error("User validation error: {0}")]
     UserValidation(String),
 
@@ -40,6 +49,13 @@ pub enum StatsigError {
     #[error("Dynamic config not found: {0}")]
     ConfigNotFound(String),
 
+    #[error("Failed to deserialize config {config_name} at {path}: {message}")]
+    ConfigDeserialization {
+        config_name: String,
+        path: String,
+        message: String,
+    },
+
     #[error("Internal error: {0}")]
     Internal(String),
 }
@@ -74,6 +90,12 @@ impl StatsigError {
         }
     }
 
+    pub fn circuit_open(retry_after: std::time::Duration) -> Self {
+        Self::CircuitOpen {
+            retry_after_ms: retry_after.as_millis() as u64,
+        }
+    }
+
     pub fn user_validation(message: impl Into<String>) -> Self {
         Self::UserValidation(message.into())
     }
@@ -86,6 +108,18 @@ impl StatsigError {
         Self::ConfigNotFound(name.into())
     }
 
+    pub fn config_deserialization(
+        config_name: impl Into<String>,
+        path: impl Into<String>,
+        message: impl Into<String>,
+    ) -> Self {
+        Self::ConfigDeserialization {
+            config_name: config_name.into(),
+            path: path.into(),
+            message: message.into(),
+        }
+    }
+
     pub fn internal(message: impl Into<String>) -> Self {
         Self::Internal(message.into())
     }
@@ -124,7 +158,12 @@ impl StatsigError {
             Self::ConfigNotFound(name) => Self::ConfigNotFound(format!("{}: {}", context, name)),
             Self::Internal(message) => Self::Internal(format!("{}: {}", context, message)),
             // These variants don't need context
-            Self::RateLimited { .. } | Self::Unauthorized => self,
+            Self::RateLimited { .. }
+            | Self::CircuitOpen { .. }
+            | Self::EventQueueFull { .. }
+            | Self::ConfigDeserialization { .. }
+            | Self::Unauthorized
+            | Self::ShutDown => self,
         }
     }
 }
@@ -137,10 +176,29 @@ impl From<reqwest::Error> for StatsigError {
 
 impl From<reqwest_middleware::Error> for StatsigError {
     fn from(err: reqwest_middleware::Error) -> Self {
+        // Errors raised by our own middleware keep their variant
+        if let Some(e) = local_error(&err) {
+            return e.clone();
+        }
         Self::Network(err.to_string())
     }
 }
 
+/// The `StatsigError` raised by our own middleware, looking through the
+/// `RetryError` wrapper added by the retry middleware
+pub(crate) fn local_error(err: &reqwest_middleware::Error) -> Option<&StatsigError> {
+    let reqwest_middleware::Error::Middleware(e) = err else {
+        return None;
+    };
+    if let Some(e) = e.downcast_ref::<StatsigError>() {
+        return Some(e);
+    }
+    match e.downcast_ref::<reqwest_retry::RetryError>()? {
+        reqwest_retry::RetryError::WithRetries { err, .. }
+        | reqwest_retry::RetryError::Error(err) => local_error(err),
+    }
+}
+
 impl StatsigError {
     pub fn is_retryable(&self) -> bool {
         match self {
@@ -150,6 +208,16 @@ impl StatsigError {
         }
     }
 
+    /// Returns `true` if the Statsig API could not be reached, failed
+    /// server-side, or was not called because of rate limiting
+    pub fn is_unavailable(&self) -> bool {
+        match self {
+            Self::Network(_) | Self::CircuitOpen { .. } | Self::RateLimited { .. } => true,
+            Self::Api { status, .. } => *status == 429 || (500..=599).contains(status),
+            _ => false,
+        }
+    }
+
     pub fn retry_after_seconds(&self) -> Option<u64> {
         match self {
             Self::RateLimited {
@@ -0,0 +1,806 @@
+//! In-process evaluation of gates and configs against downloaded config specs
+//!
+//! The evaluator mirrors Statsig's server-side rule semantics: rules are
+//! checked in order, the first rule whose conditions all pass decides the
+//! result, and its pass percentage is applied by hashing the unit ID with the
+//! spec and rule salts. Conditions that cannot be evaluated locally (ID lists,
+//! regex matching, IP/UA lookups) mark the evaluation as unsupported so the
+//! caller can fall back to the network.
+
+use crate::{
+    api::{ConfigEvaluationResult, EvaluationReason, GateEvaluationResult, LayerEvaluationResult},
+    events::ExposureEventMetadata,
+    specs::{ConfigSpec, IndexedSpecs, SpecCondition, SpecRule},
+    user::User,
+};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::cmp::Ordering;
+
+/// Maximum depth of nested `pass_gate` / `fail_gate` conditions
+const MAX_NESTING_DEPTH: usize = 32;
+
+/// Outcome of evaluating a single spec
+#[derive(Debug, Default)]
+struct SpecEvaluation {
+    bool_value: bool,
+    json_value: Value,
+    rule_id: Option<String>,
+    group_name: Option<String>,
+    secondary_exposures: Vec<ExposureEventMetadata>,
+    undelegated_secondary_exposures: Vec<ExposureEventMetadata>,
+    allocated_experiment: Option<String>,
+    explicit_parameters: Vec<String>,
+    unsupported: bool,
+}
+
+/// State accumulated while evaluating the rules of a single spec
+#[derive(Debug, Default)]
+struct EvalContext {
+    unsupported: bool,
+    secondary_exposures: Vec<ExposureEventMetadata>,
+}
+
+/// Evaluates gates and configs against a snapshot of config specs
+pub(crate) struct Evaluator<'a> {
+    specs: &'a IndexedSpecs,
+}
+
+impl<'a> Evaluator<'a> {
+    pub fn new(specs: &'a IndexedSpecs) -> Self {
+        Self { specs }
+    }
+
+    /// Evaluates a feature gate, returning `None` if it needs a server evaluation
+    pub fn check_gate(&self, user: &User, gate_name: &str) -> Option<GateEvaluationResult> {
+        let evaluation = self.evaluate_gate(user, gate_name, 0);
+        if evaluation.unsupported {
+            return None;
+        }
+
+        Some(GateEvaluationResult {
+            name: gate_name.to_string(),
+            value: evaluation.bool_value,
+            rule_id: evaluation.rule_id,
+            group_name: evaluation.group_name,
+            secondary_exposures: evaluation.secondary_exposures,
+            reason: EvaluationReason::LocalEvaluation,
+        })
+    }
+
+    /// Evaluates a dynamic config or experiment, returning `None` if it needs a server evaluation
+    pub fn get_config(&self, user: &User, config_name: &str) -> Option<ConfigEvaluationResult> {
+        // Configs created since the last sync are unknown locally
+        let spec = self.specs.configs.get(config_name)?;
+        let evaluation = self.evaluate_spec(user, spec, 0);
+        if evaluation.unsupported {
+            return None;
+        }
+
+        Some(ConfigEvaluationResult {
+            name: config_name.to_string(),
+            value: evaluation.json_value,
+            group: evaluation.rule_id.clone(),
+            rule_id: evaluation.rule_id,
+            group_name: evaluation.group_name,
+            secondary_exposures: evaluation.secondary_exposures,
+            reason: EvaluationReason::LocalEvaluation,
+        })
+    }
+
+    /// Evaluates a layer, returning `None` if it needs a server evaluation
+    pub fn get_layer(&self, user: &User, layer_name: &str) -> Option<LayerEvaluationResult> {
+        let spec = self.specs.layers.get(layer_name)?;
+        let evaluation = self.evaluate_spec(user, spec, 0);
+        if evaluation.unsupported {
+            return None;
+        }
+
+        Some(LayerEvaluationResult {
+            name: layer_name.to_string(),
+            value: evaluation.json_value,
+            rule_id: evaluation.rule_id,
+            group_name: evaluation.group_name,
+            allocated_experiment_name: evaluation.allocated_experiment,
+            explicit_parameters: evaluation.explicit_parameters,
+            secondary_exposures: evaluation.secondary_exposures,
+            undelegated_secondary_exposures: evaluation.undelegated_secondary_exposures,
+            reason: EvaluationReason::LocalEvaluation,
+        })
+    }
+
+    fn evaluate_gate(&self, user: &User, gate_name: &str, depth: usize) -> SpecEvaluation {
+        match self.specs.gates.get(gate_name) {
+            Some(spec) => self.evaluate_spec(user, spec, depth),
+            // Gates created since the last sync are unknown locally
+            None => SpecEvaluation {
+                unsupported: true,
+                ..Default::default()
+            },
+        }
+    }
+
+    fn evaluate_spec(&self, user: &User, spec: &ConfigSpec, depth: usize) -> SpecEvaluation {
+        if !spec.enabled {
+            return SpecEvaluation {
+                json_value: spec.default_value.clone(),
+                rule_id: Some("disabled".to_string()),
+                ..Default::default()
+            };
+        }
+
+        let mut ctx = EvalContext::default();
+        for rule in &spec.rules {
+            let conditions_pass = self.evaluate_rule_conditions(user, rule, depth, &mut ctx);
+            if ctx.unsupported {
+                return SpecEvaluation {
+                    unsupported: true,
+                    ..Default::default()
+                };
+            }
+            if !conditions_pass {
+                continue;
+            }
+
+            // Layers delegate allocated users to one of their experiments
+            if let Some(delegate_name) = rule.config_delegate.as_deref() {
+                if let Some(delegate) = self.specs.configs.get(delegate_name) {
+                    return self.evaluate_delegate(user, delegate, depth, ctx);
+                }
+            }
+
+            let pass = rule_passes_percentage(user, spec, rule);
+            return SpecEvaluation {
+                bool_value: pass,
+                json_value: if pass {
+                    rule.return_value.clone()
+                } else {
+                    spec.default_value.clone()
+                },
+                rule_id: Some(rule.id.clone()),
+                group_name: rule.group_name.clone(),
+                undelegated_secondary_exposures: ctx.secondary_exposures.clone(),
+                secondary_exposures: ctx.secondary_exposures,
+                ..Default::default()
+            };
+        }
+
+        SpecEvaluation {
+            json_value: spec.default_value.clone(),
+            rule_id: Some("default".to_string()),
+            undelegated_secondary_exposures: ctx.secondary_exposures.clone(),
+            secondary_exposures: ctx.secondary_exposures,
+            ..Default::default()
+        }
+    }
+
+    fn evaluate_delegate(
+        &self,
+        user: &User,
+        delegate: &ConfigSpec,
+        depth: usize,
+        ctx: EvalContext,
+    ) -> SpecEvaluation {
+        if depth >= MAX_NESTING_DEPTH {
+            return SpecEvaluation {
+                unsupported: true,
+                ..Default::default()
+            };
+        }
+
+        let mut evaluation = self.evaluate_spec(user, delegate, depth + 1);
+        if evaluation.unsupported {
+            return evaluation;
+        }
+
+        let mut secondary_exposures = ctx.secondary_exposures.clone();
+        secondary_exposures.extend(evaluation.secondary_exposures);
+        evaluation.secondary_exposures = secondary_exposures;
+        evaluation.undelegated_secondary_exposures = ctx.secondary_exposures;
+        evaluation.allocated_experiment = Some(delegate.name.clone());
+        evaluation.explicit_parameters = delegate.explicit_parameters.clone().unwrap_or_default();
+        evaluation
+    }
+
+    fn evaluate_rule_conditions(
+        &self,
+        user: &User,
+        rule: &SpecRule,
+        depth: usize,
+        ctx: &mut EvalContext,
+    ) -> bool {
+        let mut all_pass = true;
+        for condition in &rule.conditions {
+            if !self.evaluate_condition(user, condition, depth, ctx) {
+                all_pass = false;
+            }
+            if ctx.unsupported {
+                return false;
+            }
+        }
+        all_pass
+    }
+
+    fn evaluate_condition(
+        &self,
+        user: &User,
+        condition: &SpecCondition,
+        depth: usize,
+        ctx: &mut EvalContext,
+    ) -> bool {
+        let field = condition.field.as_deref().unwrap_or_default();
+        let value = match condition.condition_type.to_lowercase().as_str() {
+            "public" => return true,
+            "pass_gate" | "fail_gate" => {
+                let gate_name = condition
+                    .target_value
+                    .as_ref()
+                    .and_then(Value::as_str)
+                    .unwrap_or_default();
+                let passes = self.evaluate_nested_gate(user, gate_name, depth, ctx);
+                return if condition.condition_type.eq_ignore_ascii_case("pass_gate") {
+                    passes
+                } else {
+                    !passes
+                };
+            }
+            "multi_pass_gate" | "multi_fail_gate" => {
+                let gate_names = condition
+                    .target_value
+                    .as_ref()
+                    .and_then(Value::as_array)
+                    .cloned()
+                    .unwrap_or_default();
+                let is_pass = condition
+                    .condition_type
+                    .eq_ignore_ascii_case("multi_pass_gate");
+                for gate_name in gate_names.iter().filter_map(Value::as_str) {
+                    let passes = self.evaluate_nested_gate(user, gate_name, depth, ctx);
+                    if ctx.unsupported {
+                        return false;
+                    }
+                    if passes == is_pass {
+                        return true;
+                    }
+                }
+                return false;
+            }
+            "user_field" => user_field_value(user, field),
+            "ip_based" => {
+                let value = user_field_value(user, field);
+                if value.is_none() && user.ip.is_some() {
+                    // Resolving fields from an IP address requires a geo lookup
+                    ctx.unsupported = true;
+                    return false;
+                }
+                value
+            }
+            "ua_based" => {
+                let value = user_field_value(user, field);
+                if value.is_none() && user.user_agent.is_some() {
+                    // Resolving fields from a user agent requires UA parsing
+                    ctx.unsupported = true;
+                    return false;
+                }
+                value
+            }
+            "environment_field" => environment_field_value(user, field),
+            "current_time" => Some(Value::from(crate::now_ms())),
+            "user_bucket" => {
+                let salt = condition
+                    .additional_values
+                    .as_ref()
+                    .and_then(|values| values.get("salt"))
+                    .and_then(Value::as_str)
+                    .unwrap_or_default();
+                let unit_id = unit_id(user, &condition.id_type).unwrap_or_default();
+                Some(Value::from(
+                    hash_string(&format!("{salt}.{unit_id}")) % 1000,
+                ))
+            }
+            "unit_id" => unit_id(user, &condition.id_type).map(Value::from),
+            _ => {
+                ctx.unsupported = true;
+                return false;
+            }
+        };
+
+        let operator = condition.operator.as_deref().unwrap_or_default();
+        match evaluate_operator(operator, value.as_ref(), condition.target_value.as_ref()) {
+            Some(pass) => pass,
+            None => {
+                ctx.unsupported = true;
+                false
+            }
+        }
+    }
+
+    fn evaluate_nested_gate(
+        &self,
+        user: &User,
+        gate_name: &str,
+        depth: usize,
+        ctx: &mut EvalContext,
+    ) -> bool {
+        if depth >= MAX_NESTING_DEPTH {
+            ctx.unsupported = true;
+            return false;
+        }
+
+        let evaluation = self.evaluate_gate(user, gate_name, depth + 1);
+        if evaluation.unsupported {
+            ctx.unsupported = true;
+            return false;
+        }
+
+        ctx.secondary_exposures
+            .extend(evaluation.secondary_exposures);
+        ctx.secondary_exposures.push(ExposureEventMetadata {
+            gate: gate_name.to_string(),
+            gate_value: evaluation.bool_value.to_string(),
+            rule_id: evaluation.rule_id.unwrap_or_default(),
+        });
+        evaluation.bool_value
+    }
+}
+
+/// Applies an operator, returning `None` if the operator is not supported locally
+fn evaluate_operator(
+    operator: &str,
+    value: Option<&Value>,
+    target: Option<&Value>,
+) -> Option<bool> {
+    let pass = match operator.to_lowercase().as_str() {
+        "gt" => compare_numbers(value, target, |o| o == Ordering::Greater),
+        "gte" => compare_numbers(value, target, |o| o != Ordering::Less),
+        "lt" => compare_numbers(value, target, |o| o == Ordering::Less),
+        "lte" => compare_numbers(value, target, |o| o != Ordering::Greater),
+        "version_gt" => compare_versions(value, target, |o| o == Ordering::Greater),
+        "version_gte" => compare_versions(value, target, |o| o != Ordering::Less),
+        "version_lt" => compare_versions(value, target, |o| o == Ordering::Less),
+        "version_lte" => compare_versions(value, target, |o| o != Ordering::Greater),
+        "version_eq" => compare_versions(value, target, |o| o == Ordering::Equal),
+        "version_neq" => compare_versions(value, target, |o| o != Ordering::Equal),
+        "any" => match_any(value, target, false, |v, t| v == t),
+        "none" => !match_any(value, target, false, |v, t| v == t),
+        "any_case_sensitive" => match_any(value, target, true, |v, t| v == t),
+        "none_case_sensitive" => !match_any(value, target, true, |v, t| v == t),
+        "str_starts_with_any" => match_any(value, target, false, |v, t| v.starts_with(t)),
+        "str_ends_with_any" => match_any(value, target, false, |v, t| v.ends_with(t)),
+        "str_contains_any" => match_any(value, target, false, |v, t| v.contains(t)),
+        "str_contains_none" => !match_any(value, target, false, |v, t| v.contains(t)),
+        "eq" => value.unwrap_or(&Value::Null) == target.unwrap_or(&Value::Null),
+        "neq" => value.unwrap_or(&Value::Null) != target.unwrap_or(&Value::Null),
+        "before" => compare_times(value, target, |v, t| v < t)?,
+        "after" => compare_times(value, target, |v, t| v > t)?,
+        "on" => compare_times(value, target, |v, t| v / DAY_MS == t / DAY_MS)?,
+        _ => return None,
+    };
+    Some(pass)
+}
+
+const DAY_MS: i64 = 24 * 60 * 60 * 1000;
+
+fn as_number(value: &Value) -> Option<f64> {
+    match value {
+        Value::Number(n) => n.as_f64(),
+        Value::String(s) => s.trim().parse().ok(),
+        _ => None,
+    }
+}
+
+fn as_string(value: &Value) -> Option<String> {
+    match value {
+        Value::String(s) => Some(s.clone()),
+        Value::Number(n) => Some(n.to_string()),
+        Value::Bool(b) => Some(b.to_string()),
+        _ => None,
+    }
+}
+
+fn compare_numbers(
+    value: Option<&Value>,
+    target: Option<&Value>,
+    check: impl Fn(Ordering) -> bool,
+) -> bool {
+    match (value.and_then(as_number), target.and_then(as_number)) {
+        (Some(v), Some(t)) => v.partial_cmp(&t).is_some_and(check),
+        _ => false,
+    }
+}
+
+fn compare_versions(
+    value: Option<&Value>,
+    target: Option<&Value>,
+    check: impl Fn(Ordering) -> bool,
+) -> bool {
+    fn parse(version: &str) -> Option<Vec<u64>> {
+        let core = version.split('-').next().unwrap_or_default();
+        core.split('.')
+            .map(|part| part.trim().parse().ok())
+            .collect()
+    }
+
+    let (Some(v), Some(t)) = (
+        value.and_then(as_string).as_deref().and_then(parse),
+        target.and_then(as_string).as_deref().and_then(parse),
+    ) else {
+        return false;
+    };
+
+    let len = v.len().max(t.len());
+    let pad = |parts: &[u64]| {
+        let mut padded = parts.to_vec();
+        padded.resize(len, 0);
+        padded
+    };
+    check(pad(&v).cmp(&pad(&t)))
+}
+
+fn match_any(
+    value: Option<&Value>,
+    target: Option<&Value>,
+    case_sensitive: bool,
+    matches: impl Fn(&str, &str) -> bool,
+) -> bool {
+    let Some(value) = value.and_then(as_string) else {
+        return false;
+    };
+    let normalize = |s: String| if case_sensitive { s } else { s.to_lowercase() };
+    let value = normalize(value);
+
+    let targets: Vec<String> = match target {
+        Some(Value::Array(items)) => items.iter().filter_map(as_string).collect(),
+        Some(other) => as_string(other).into_iter().collect(),
+        None => Vec::new(),
+    };
+
+    targets
+        .into_iter()
+        .map(normalize)
+        .any(|t| matches(&value, &t))
+}
+
+/// Compares timestamps given in unix seconds or milliseconds
+///
+/// Returns `None` for non-numeric values such as ISO date strings.
+fn compare_times(
+    value: Option<&Value>,
+    target: Option<&Value>,
+    check: impl Fn(i64, i64) -> bool,
+) -> Option<bool> {
+    fn to_millis(value: &Value) -> Option<Option<i64>> {
+        match value {
+            Value::Null => Some(None),
+            other => {
+                let n = as_number(other)? as i64;
+                // Values below 10^11 are treated as seconds
+                Some(Some(if n < 100_000_000_000 { n * 1000 } else { n }))
+            }
+        }
+    }
+
+    let value = to_millis(value.unwrap_or(&Value::Null))?;
+    let target = to_millis(target.unwrap_or(&Value::Null))?;
+    Some(match (value, target) {
+        (Some(v), Some(t)) => check(v, t),
+        _ => false,
+    })
+}
+
+fn rule_passes_percentage(user: &User, spec: &ConfigSpec, rule: &SpecRule) -> bool {
+    if rule.pass_percentage >= 100.0 {
+        return true;
+    }
+    if rule.pass_percentage <= 0.0 {
+        return false;
+    }
+
+    let rule_salt = rule.salt.as_deref().unwrap_or(&rule.id);
+    let unit_id = unit_id(user, &rule.id_type).unwrap_or_default();
+    let hash = hash_string(&format!("{}.{}.{}", spec.salt, rule_salt, unit_id));
+    ((hash % 10_000) as f64) < rule.pass_percentage * 100.0
+}
+
+fn hash_string(input: &str) -> u64 {
+    let digest = Sha256::digest(input.as_bytes());
+    let mut bytes = [0u8; 8];
+    bytes.copy_from_slice(&digest[..8]);
+    u64::from_be_bytes(bytes)
+}
+
+fn unit_id(user: &User, id_type: &str) -> Option<String> {
+    if id_type.is_empty() || id_type.eq_ignore_ascii_case("userid") {
+        return user.user_id.clone();
+    }
+
+    user.custom_ids.as_ref().and_then(|ids| {
+        ids.iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(id_type))
+            .map(|(_, value)| value.clone())
+    })
+}
+
+fn user_field_value(user: &User, field: &str) -> Option<Value> {
+    let builtin = match field.to_lowercase().as_str() {
+        "userid" | "user_id" => user.user_id.clone(),
+        "email" => user.email.clone(),
+        "ip" | "ipaddress" | "ip_address" => user.ip.clone(),
+        "useragent" | "user_agent" => user.user_agent.clone(),
+        "country" => user.country.clone(),
+        "locale" => user.locale.clone(),
+        "appversion" | "app_version" => user.app_version.clone(),
+        _ => None,
+    };
+    if let Some(value) = builtin {
+        return Some(Value::String(value));
+    }
+
+    let lookup = |attributes: &Option<std::collections::HashMap<String, Value>>| {
+        attributes.as_ref().and_then(|attrs| {
+            attrs.get(field).cloned().or_else(|| {
+                attrs
+                    .iter()
+                    .find(|(key, _)| key.eq_ignore_ascii_case(field))
+                    .map(|(_, value)| value.clone())
+            })
+        })
+    };
+
+    lookup(&user.custom).or_else(|| lookup(&user.private_attributes))
+}
+
+fn environment_field_value(user: &User, field: &str) -> Option<Value> {
+    if !field.eq_ignore_ascii_case("tier") {
+        return None;
+    }
+    user.statsig_environment
+        .as_ref()
+        .and_then(|env| serde_json::to_value(&env.tier).ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::specs::ConfigSpecs;
+    use serde_json::json;
+
+    fn specs() -> IndexedSpecs {
+        let specs: ConfigSpecs = serde_json::from_value(json!({
+            "has_updates": true,
+            "time": 1,
+            "feature_gates": [
+                {
+                    "name": "employees_only",
+                    "type": "feature_gate",
+                    "salt": "salt-a",
+                    "enabled": true,
+                    "defaultValue": false,
+                    "rules": [{
+                        "name": "employees",
+                        "id": "rule-employees",
+                        "passPercentage": 100,
+                        "returnValue": true,
+                        "idType": "userID",
+                        "conditions": [{
+                            "type": "user_field",
+                            "field": "email",
+                            "operator": "str_ends_with_any",
+                            "targetValue": ["@example.com"],
+                            "idType": "userID"
+                        }]
+                    }]
+                },
+                {
+                    "name": "nested_gate",
+                    "type": "feature_gate",
+                    "salt": "salt-b",
+                    "enabled": true,
+                    "defaultValue": false,
+                    "rules": [{
+                        "name": "pass employees",
+                        "id": "rule-nested",
+                        "passPercentage": 100,
+                        "returnValue": true,
+                        "idType": "userID",
+                        "conditions": [{
+                            "type": "pass_gate",
+                            "targetValue": "employees_only",
+                            "idType": "userID"
+                        }]
+                    }]
+                },
+                {
+                    "name": "segment_gate",
+                    "type": "feature_gate",
+                    "salt": "salt-c",
+                    "enabled": true,
+                    "defaultValue": false,
+                    "rules": [{
+                        "name": "segment",
+                        "id": "rule-segment",
+                        "passPercentage": 100,
+                        "returnValue": true,
+                        "idType": "userID",
+                        "conditions": [{
+                            "type": "unit_id",
+                            "operator": "in_segment_list",
+                            "targetValue": "list",
+                            "idType": "userID"
+                        }]
+                    }]
+                }
+            ],
+            "layer_configs": [{
+                "name": "checkout_layer",
+                "type": "dynamic_config",
+                "salt": "salt-layer",
+                "enabled": true,
+                "defaultValue": {"button_color": "blue", "show_banner": false},
+                "rules": [{
+                    "name": "experiment allocation",
+                    "id": "rule-layer",
+                    "passPercentage": 100,
+                    "returnValue": {},
+                    "idType": "userID",
+                    "configDelegate": "checkout_experiment",
+                    "conditions": [{"type": "public", "idType": "userID"}]
+                }]
+            }],
+            "dynamic_configs": [{
+                "name": "checkout_experiment",
+                "type": "dynamic_config",
+                "entity": "experiment",
+                "salt": "salt-exp",
+                "enabled": true,
+                "defaultValue": {},
+                "explicitParameters": ["button_color"],
+                "rules": [{
+                    "name": "treatment",
+                    "id": "rule-treatment",
+                    "passPercentage": 100,
+                    "returnValue": {"button_color": "green", "show_banner": false},
+                    "idType": "userID",
+                    "groupName": "Treatment",
+                    "isExperimentGroup": true,
+                    "conditions": [{"type": "public", "idType": "userID"}]
+                }]
+            }, {
+                "name": "ui_settings",
+                "type": "dynamic_config",
+                "salt": "salt-d",
+                "enabled": true,
+                "defaultValue": {"theme": "light"},
+                "rules": [{
+                    "name": "newer apps",
+                    "id": "rule-apps",
+                    "passPercentage": 100,
+                    "returnValue": {"theme": "dark"},
+                    "idType": "userID",
+                    "groupName": "Dark",
+                    "conditions": [{
+                        "type": "user_field",
+                        "field": "appVersion",
+                        "operator": "version_gte",
+                        "targetValue": "2.1",
+                        "idType": "userID"
+                    }]
+                }]
+            }]
+        }))
+        .unwrap();
+        specs.into()
+    }
+
+    #[test]
+    fn evaluates_gate_conditions() {
+        let specs = specs();
+        let evaluator = Evaluator::new(&specs);
+
+        let employee = User::builder().email("a@example.com").build().unwrap();
+        let outsider = User::builder().email("a@other.com").build().unwrap();
+
+        let result = evaluator.check_gate(&employee, "employees_only").unwrap();
+        assert!(result.value);
+        assert_eq!(result.rule_id.as_deref(), Some("rule-employees"));
+        assert!(
+            !evaluator
+                .check_gate(&outsider, "employees_only")
+                .unwrap()
+                .value
+        );
+        assert!(
+            evaluator
+                .check_gate(&employee, "nested_gate")
+                .unwrap()
+                .value
+        );
+    }
+
+    #[test]
+    fn unknown_specs_defer_to_server() {
+        let specs = specs();
+        let evaluator = Evaluator::new(&specs);
+        let user = User::builder().user_id("u1").build().unwrap();
+
+        assert!(evaluator.check_gate(&user, "missing_gate").is_none());
+        assert!(evaluator.get_config(&user, "missing_config").is_none());
+        assert!(evaluator.get_layer(&user, "missing_layer").is_none());
+    }
+
+    #[test]
+    fn unsupported_conditions_defer_to_server() {
+        let specs = specs();
+        let evaluator = Evaluator::new(&specs);
+        let user = User::builder().user_id("u1").build().unwrap();
+
+        assert!(evaluator.check_gate(&user, "segment_gate").is_none());
+    }
+
+    #[test]
+    fn evaluates_config_rules() {
+        let specs = specs();
+        let evaluator = Evaluator::new(&specs);
+
+        let new_app = User::builder().app_version("2.10.0").build().unwrap();
+        let old_app = User::builder().app_version("2.0.9").build().unwrap();
+
+        let result = evaluator.get_config(&new_app, "ui_settings").unwrap();
+        assert_eq!(result.value, json!({"theme": "dark"}));
+        assert_eq!(result.group_name.as_deref(), Some("Dark"));
+        let result = evaluator.get_config(&old_app, "ui_settings").unwrap();
+        assert_eq!(result.value, json!({"theme": "light"}));
+        assert_eq!(result.rule_id.as_deref(), Some("default"));
+    }
+
+    #[test]
+    fn layers_delegate_to_experiments() {
+        let specs = specs();
+        let evaluator = Evaluator::new(&specs);
+        let user = User::builder().user_id("u1").build().unwrap();
+
+        let layer = evaluator.get_layer(&user, "checkout_layer").unwrap();
+        assert_eq!(
+            layer.allocated_experiment_name.as_deref(),
+            Some("checkout_experiment")
+        );
+        assert_eq!(layer.explicit_parameters, vec!["button_color".to_string()]);
+        assert_eq!(layer.value["button_color"], "green");
+        assert_eq!(layer.group_name.as_deref(), Some("Treatment"));
+    }
+
+    #[test]
+    fn pass_percentage_is_deterministic() {
+        let spec: ConfigSpec = serde_json::from_value(json!({
+            "name": "rollout",
+            "type": "feature_gate",
+            "salt": "rollout-salt",
+            "enabled": true,
+            "defaultValue": false,
+            "rules": []
+        }))
+        .unwrap();
+        let rule: SpecRule = serde_json::from_value(json!({
+            "name": "half",
+            "id": "rule-half",
+            "passPercentage": 50,
+            "idType": "userID"
+        }))
+        .unwrap();
+
+        let passed = (0..1000)
+            .filter(|i| {
+                let user = User::builder()
+                    .user_id(format!("user-{i}"))
+                    .build()
+                    .unwrap();
+                rule_passes_percentage(&user, &spec, &rule)
+            })
+            .count();
+        assert!((400..600).contains(&passed));
+
+        let user = User::builder().user_id("stable").build().unwrap();
+        let first = rule_passes_percentage(&user, &spec, &rule);
+        assert_eq!(first, rule_passes_percentage(&user, &spec, &rule));
+    }
+}
@@ -0,0 +1,227 @@
+//! Background event logging
+//!
+//! Events are enqueued without blocking and flushed to `/v1/log_event` by a
+//! background task once `event_flush_size` events are buffered or every
+//! `event_flush_interval`. Failed flushes are retried on later ticks.
+
+use crate::{
+    config::{EventDropPolicy, StatsigClientConfig},
+    error::{Result, StatsigError},
+    events::StatsigEvent,
+    transport::Transport,
+};
+use std::collections::VecDeque;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use tokio::sync::mpsc;
+use tracing::{error, info, warn};
+
+/// Non-blocking handle used to enqueue events for the [`EventLogger`]
+#[derive(Debug, Clone)]
+pub struct EventQueue {
+    sender: mpsc::Sender<StatsigEvent>,
+    capacity: usize,
+    drop_policy: EventDropPolicy,
+    dropped: Arc<AtomicU64>,
+}
+
+impl EventQueue {
+    /// Creates a new queue and the receiver to hand to an [`EventLogger`]
+    pub fn new(
+        capacity: usize,
+        drop_policy: EventDropPolicy,
+    ) -> (Self, mpsc::Receiver<StatsigEvent>) {
+        let (sender, receiver) = mpsc::channel(capacity);
+        let queue = Self {
+            sender,
+            capacity,
+            drop_policy,
+            dropped: Arc::new(AtomicU64::new(0)),
+        };
+        (queue, receiver)
+    }
+
+    /// Enqueues an event, applying the drop policy if the queue is full
+    pub fn enqueue(&self, event: StatsigEvent) -> Result<()> {
+        match self.sender.try_send(event) {
+            Ok(()) => Ok(()),
+            Err(mpsc::error::TrySendError::Full(event)) => {
+                self.dropped.fetch_add(1, Ordering::Relaxed);
+                match self.drop_policy {
+                    EventDropPolicy::DropNewest => {
+                        warn!("Event queue full, dropping event {}", event.event_name);
+                        Ok(())
+                    }
+                    EventDropPolicy::Reject => Err(StatsigError::EventQueueFull {
+                        capacity: self.capacity,
+                    }),
+                }
+            }
+            Err(mpsc::error::TrySendError::Closed(_)) => {
+                Err(StatsigError::internal("Event logger channel closed"))
+            }
+        }
+    }
+
+    /// Number of events dropped or rejected because the queue was full
+    pub fn dropped_count(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+}
+
+/// A batch of events whose flush failed and will be retried
+struct PendingFlush {
+    events: Vec<StatsigEvent>,
+    attempts: u32,
+}
+
+/// Buffers events and flushes them to the Statsig API in the background
+pub struct EventLogger {
+    receiver: mpsc::Receiver<StatsigEvent>,
+    shutdown_rx: tokio::sync::broadcast::Receiver<()>,
+}
+
+impl EventLogger {
+    /// Creates a new event logger
+    pub fn new(
+        receiver: mpsc::Receiver<StatsigEvent>,
+        shutdown_rx: tokio::sync::broadcast::Receiver<()>,
+    ) -> Self {
+        Self {
+            receiver,
+            shutdown_rx,
+        }
+    }
+
+    /// Runs the event logger loop
+    pub async fn run(mut self, transport: Arc<dyn Transport>, config: StatsigClientConfig) {
+        let mut interval = tokio::time::interval(config.event_flush_interval);
+        let mut buffer = Vec::new();
+        let mut retries = VecDeque::new();
+
+        loop {
+            tokio::select! {
+                Some(event) = self.receiver.recv() => {
+                    buffer.push(event);
+
+                    // Flush if batch size reached
+                    if buffer.len() >= config.event_flush_size {
+                        Self::flush(transport.as_ref(), &config, &mut buffer, &mut retries).await;
+                    }
+                }
+                _ = interval.tick() => {
+                    Self::retry_failed(transport.as_ref(), &config, &mut retries).await;
+                    Self::flush(transport.as_ref(), &config, &mut buffer, &mut retries).await;
+                }
+                _ = self.shutdown_rx.recv() => {
+                    info!("Event logger shutting down");
+
+                    // Stop accepting events and flush everything still queued
+                    self.receiver.close();
+                    while let Some(event) = self.receiver.recv().await {
+                        buffer.push(event);
+                    }
+                    Self::retry_failed(transport.as_ref(), &config, &mut retries).await;
+                    Self::flush(transport.as_ref(), &config, &mut buffer, &mut retries).await;
+                    break;
+                }
+            }
+        }
+    }
+
+    /// Sends all buffered events, queueing them for retry on failure
+    async fn flush(
+        transport: &dyn Transport,
+        config: &StatsigClientConfig,
+        buffer: &mut Vec<StatsigEvent>,
+        retries: &mut VecDeque<PendingFlush>,
+    ) {
+        if buffer.is_empty() {
+            return;
+        }
+
+        let events = std::mem::take(buffer);
+        if let Err(e) = transport.log_events(None, &events).await {
+            error!("Failed to flush {} events: {:?}", events.len(), e);
+            Self::schedule_retry(
+                config,
+                retries,
+                PendingFlush {
+                    events,
+                    attempts: 1,
+                },
+            );
+        }
+    }
+
+    /// Retries previously failed flushes
+    async fn retry_failed(
+        transport: &dyn Transport,
+        config: &StatsigClientConfig,
+        retries: &mut VecDeque<PendingFlush>,
+    ) {
+        for mut pending in std::mem::take(retries) {
+            if let Err(e) = transport.log_events(None, &pending.events).await {
+                error!(
+                    "Retry {} failed for {} events: {:?}",
+                    pending.attempts,
+                    pending.events.len(),
+                    e
+                );
+                pending.attempts += 1;
+                Self::schedule_retry(config, retries, pending);
+            }
+        }
+    }
+
+    fn schedule_retry(
+        config: &StatsigClientConfig,
+        retries: &mut VecDeque<PendingFlush>,
+        pending: PendingFlush,
+    ) {
+        if pending.attempts > config.event_max_flush_retries {
+            warn!(
+                "Dropping {} events after {} failed flushes",
+                pending.events.len(),
+                pending.attempts
+            );
+            return;
+        }
+
+        retries.push_back(pending);
+
+        // Bound memory held by failed batches to the queue capacity
+        let mut retained: usize = retries.iter().map(|p| p.events.len()).sum();
+        while retained > config.event_queue_capacity {
+            match retries.pop_front() {
+                Some(dropped) => {
+                    warn!("Dropping {} events awaiting retry", dropped.events.len());
+                    retained -= dropped.events.len();
+                }
+                None => break,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn full_queue_applies_drop_policy() {
+        let event = || StatsigEvent::builder().event_name("click").build();
+
+        let (queue, _receiver) = EventQueue::new(1, EventDropPolicy::DropNewest);
+        queue.enqueue(event()).unwrap();
+        queue.enqueue(event()).unwrap();
+        assert_eq!(queue.dropped_count(), 1);
+
+        let (queue, _receiver) = EventQueue::new(1, EventDropPolicy::Reject);
+        queue.enqueue(event()).unwrap();
+        assert!(matches!(
+            queue.enqueue(event()),
+            Err(StatsigError::EventQueueFull { capacity: 1 })
+        ));
+    }
+}
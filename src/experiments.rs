@@ -0,0 +1,105 @@
+//! Experiment and layer evaluation types
+//!
+//! [`Experiment`] wraps a dynamic config evaluation that belongs to an
+//! experiment. [`Layer`] wraps a layer evaluation and logs a layer exposure
+//! only for the parameters that are actually read, which is how Statsig
+//! attributes layer exposures to the allocated experiment.
+
+use crate::{
+    api::{ConfigEvaluationResult, EvaluationReason, LayerEvaluationResult},
+    exposures::ExposureLogger,
+    user::User,
+};
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+
+/// An experiment evaluation for a user
+#[derive(Debug, Clone)]
+pub struct Experiment {
+    pub name: String,
+    pub value: Value,
+    pub rule_id: Option<String>,
+    pub group_name: Option<String>,
+    pub group: Option<String>,
+    pub reason: EvaluationReason,
+}
+
+impl Experiment {
+    /// Reads a parameter, returning `default` if it is missing or has a different type
+    pub fn get<T: DeserializeOwned>(&self, parameter_name: &str, default: T) -> T {
+        get_parameter(&self.value, parameter_name).unwrap_or(default)
+    }
+}
+
+impl From<ConfigEvaluationResult> for Experiment {
+    fn from(result: ConfigEvaluationResult) -> Self {
+        Self {
+            name: result.name,
+            value: result.value,
+            rule_id: result.rule_id,
+            group_name: result.group_name,
+            group: result.group,
+            reason: result.reason,
+        }
+    }
+}
+
+/// A layer evaluation for a user
+///
+/// Parameter values are only reachable through [`Layer::get`], so that every
+/// read is attributed with a layer exposure for that parameter.
+#[derive(Debug, Clone)]
+pub struct Layer {
+    pub name: String,
+    pub rule_id: Option<String>,
+    pub group_name: Option<String>,
+    pub allocated_experiment_name: Option<String>,
+    pub reason: EvaluationReason,
+    evaluation: LayerEvaluationResult,
+    user: User,
+    exposure_logger: Option<ExposureLogger>,
+}
+
+impl Layer {
+    pub(crate) fn new(
+        evaluation: LayerEvaluationResult,
+        user: User,
+        exposure_logger: Option<ExposureLogger>,
+    ) -> Self {
+        Self {
+            name: evaluation.name.clone(),
+            rule_id: evaluation.rule_id.clone(),
+            group_name: evaluation.group_name.clone(),
+            allocated_experiment_name: evaluation.allocated_experiment_name.clone(),
+            reason: evaluation.reason,
+            evaluation,
+            user,
+            exposure_logger,
+        }
+    }
+
+    /// Reads a parameter, returning `default` if it is missing or has a different type
+    ///
+    /// A layer exposure is logged for `parameter_name` when the parameter exists.
+    pub fn get<T: DeserializeOwned>(&self, parameter_name: &str, default: T) -> T {
+        match get_parameter(&self.evaluation.value, parameter_name) {
+            Some(value) => {
+                if let Some(exposure_logger) = &self.exposure_logger {
+                    exposure_logger.log_layer_exposure(
+                        &self.user,
+                        &self.evaluation,
+                        parameter_name,
+                    );
+                }
+                value
+            }
+            None => default,
+        }
+    }
+}
+
+pub(crate) fn get_parameter<T: DeserializeOwned>(value: &Value, parameter_name: &str) -> Option<T> {
+    value
+        .get(parameter_name)
+        .and_then(|parameter| T::deserialize(parameter).ok())
+}
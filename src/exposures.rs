@@ -0,0 +1,303 @@
+//! Automatic exposure logging
+//!
+//! Every gate and config evaluation served to a caller is reported to Statsig
+//! as a `statsig::gate_exposure` or `statsig::config_exposure` event, including
+//! cache hits and local evaluations. Layer parameters are reported as
+//! `statsig::layer_exposure` events when they are read. Identical exposures for the same user,
+//! entity, rule and value are deduplicated within a time window.
+
+use crate::{
+    api::{ConfigEvaluationResult, EvaluationReason, GateEvaluationResult, LayerEvaluationResult},
+    event_logger::EventQueue,
+    events::{ExposureEventMetadata, StatsigEvent, StatsigEventTime},
+    user::User,
+};
+use moka::sync::Cache;
+use std::collections::HashMap;
+use std::time::Duration;
+use tracing::warn;
+
+pub(crate) const GATE_EXPOSURE_EVENT: &str = "statsig::gate_exposure";
+pub(crate) const CONFIG_EXPOSURE_EVENT: &str = "statsig::config_exposure";
+pub(crate) const LAYER_EXPOSURE_EVENT: &str = "statsig::layer_exposure";
+
+/// Maximum number of distinct exposures remembered for deduplication
+const DEDUPE_CAPACITY: u64 = 100_000;
+
+/// Builds exposure events and enqueues them on the event queue
+#[derive(Debug, Clone)]
+pub(crate) struct ExposureLogger {
+    queue: EventQueue,
+    dedupe: Cache<String, ()>,
+    disabled: bool,
+}
+
+impl ExposureLogger {
+    pub fn new(queue: EventQueue, dedupe_window: Duration, disabled: bool) -> Self {
+        let dedupe = Cache::builder()
+            .time_to_live(dedupe_window)
+            .max_capacity(DEDUPE_CAPACITY)
+            .build();
+
+        Self {
+            queue,
+            dedupe,
+            disabled,
+        }
+    }
+
+    /// Logs a gate exposure unless an identical one was logged recently
+    pub fn log_gate_exposure(&self, user: &User, result: &GateEvaluationResult) {
+        self.gate_exposure(user, result, false);
+    }
+
+    /// Logs a manually triggered gate exposure
+    ///
+    /// Manual exposures are logged even when automatic exposure logging is
+    /// disabled and are never deduplicated.
+    pub fn log_manual_gate_exposure(&self, user: &User, result: &GateEvaluationResult) {
+        self.gate_exposure(user, result, true);
+    }
+
+    /// Logs a config exposure unless an identical one was logged recently
+    pub fn log_config_exposure(&self, user: &User, result: &ConfigEvaluationResult) {
+        self.config_exposure(user, result, false);
+    }
+
+    /// Logs a manually triggered config exposure
+    pub fn log_manual_config_exposure(&self, user: &User, result: &ConfigEvaluationResult) {
+        self.config_exposure(user, result, true);
+    }
+
+    /// Logs a layer exposure for a single parameter unless logged recently
+    ///
+    /// Only explicit parameters of the allocated experiment are attributed to
+    /// it; other parameters are attributed to the layer itself.
+    pub fn log_layer_exposure(
+        &self,
+        user: &User,
+        result: &LayerEvaluationResult,
+        parameter_name: &str,
+    ) {
+        if !self.should_log(result.reason, false) {
+            return;
+        }
+
+        let is_explicit = result
+            .explicit_parameters
+            .iter()
+            .any(|name| name == parameter_name);
+        let allocated_experiment = if is_explicit {
+            result.allocated_experiment_name.clone().unwrap_or_default()
+        } else {
+            String::new()
+        };
+        let secondary_exposures = if is_explicit {
+            &result.secondary_exposures
+        } else {
+            &result.undelegated_secondary_exposures
+        };
+
+        let rule_id = result.rule_id.clone().unwrap_or_default();
+        let dedupe_name = format!("{}:{}", result.name, parameter_name);
+        if !self.first_seen(user, "layer", &dedupe_name, &rule_id, &allocated_experiment) {
+            return;
+        }
+
+        let metadata = HashMap::from([
+            ("config".to_string(), result.name.clone()),
+            ("ruleID".to_string(), rule_id),
+            ("allocatedExperiment".to_string(), allocated_experiment),
+            ("parameterName".to_string(), parameter_name.to_string()),
+            ("isExplicitParameter".to_string(), is_explicit.to_string()),
+        ]);
+        self.enqueue(
+            LAYER_EXPOSURE_EVENT,
+            user,
+            metadata,
+            secondary_exposures,
+            false,
+        );
+    }
+
+    fn gate_exposure(&self, user: &User, result: &GateEvaluationResult, is_manual: bool) {
+        if !self.should_log(result.reason, is_manual) {
+            return;
+        }
+
+        let rule_id = result.rule_id.clone().unwrap_or_default();
+        let gate_value = result.value.to_string();
+        if !is_manual && !self.first_seen(user, "gate", &result.name, &rule_id, &gate_value) {
+            return;
+        }
+
+        let metadata = HashMap::from([
+            ("gate".to_string(), result.name.clone()),
+            ("gateValue".to_string(), gate_value),
+            ("ruleID".to_string(), rule_id),
+        ]);
+        self.enqueue(
+            GATE_EXPOSURE_EVENT,
+            user,
+            metadata,
+            &result.secondary_exposures,
+            is_manual,
+        );
+    }
+
+    fn config_exposure(&self, user: &User, result: &ConfigEvaluationResult, is_manual: bool) {
+        if !self.should_log(result.reason, is_manual) {
+            return;
+        }
+
+        let rule_id = result.rule_id.clone().unwrap_or_default();
+        if !is_manual && !self.first_seen(user, "config", &result.name, &rule_id, "") {
+            return;
+        }
+
+        let metadata = HashMap::from([
+            ("config".to_string(), result.name.clone()),
+            ("ruleID".to_string(), rule_id),
+        ]);
+        self.enqueue(
+            CONFIG_EXPOSURE_EVENT,
+            user,
+            metadata,
+            &result.secondary_exposures,
+            is_manual,
+        );
+    }
+
+    fn should_log(&self, reason: EvaluationReason, is_manual: bool) -> bool {
+        // Configured defaults and local overrides were not assigned by any rule
+        (is_manual || !self.disabled)
+            && !matches!(
+                reason,
+                EvaluationReason::Default | EvaluationReason::Override
+            )
+    }
+
+    /// Returns `true` the first time an exposure is seen within the dedupe window
+    fn first_seen(&self, user: &User, kind: &str, name: &str, rule_id: &str, value: &str) -> bool {
+        let key = format!(
+            "{}|{}|{}|{}|{}",
+            kind,
+            name,
+            rule_id,
+            value,
+            user.hash_for_cache()
+        );
+        self.dedupe.entry(key).or_insert(()).is_fresh()
+    }
+
+    fn enqueue(
+        &self,
+        event_name: &str,
+        user: &User,
+        mut metadata: HashMap<String, String>,
+        secondary_exposures: &[ExposureEventMetadata],
+        is_manual: bool,
+    ) {
+        if is_manual {
+            metadata.insert("isManualExposure".to_string(), "true".to_string());
+        }
+
+        let event = StatsigEvent::builder()
+            .event_name(event_name)
+            .time(StatsigEventTime::UnixMillis(crate::now_ms()))
+            .user(user.clone())
+            .metadata(metadata)
+            .secondary_exposures(secondary_exposures.to_vec())
+            .build();
+
+        if let Err(e) = self.queue.enqueue(event) {
+            warn!("Failed to enqueue {} event: {}", event_name, e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::EventDropPolicy;
+
+    #[tokio::test]
+    async fn deduplicates_identical_exposures() {
+        let (queue, mut receiver) = EventQueue::new(10, EventDropPolicy::DropNewest);
+        let logger = ExposureLogger::new(queue, Duration::from_secs(60), false);
+        let user = User::builder().user_id("u1").build().unwrap();
+        let gate = |value| GateEvaluationResult {
+            name: "my_gate".to_string(),
+            value,
+            rule_id: Some("rule".to_string()),
+            group_name: None,
+            secondary_exposures: Vec::new(),
+            reason: EvaluationReason::Cache,
+        };
+
+        logger.log_gate_exposure(&user, &gate(true));
+        logger.log_gate_exposure(&user, &gate(true));
+        logger.log_gate_exposure(&user, &gate(false));
+
+        let first = receiver.try_recv().unwrap();
+        assert_eq!(first.event_name, GATE_EXPOSURE_EVENT);
+        assert_eq!(first.metadata.unwrap()["gateValue"], "true");
+        let second = receiver.try_recv().unwrap();
+        assert_eq!(second.metadata.unwrap()["gateValue"], "false");
+        assert!(receiver.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn manual_exposures_bypass_disabled_logging() {
+        let (queue, mut receiver) = EventQueue::new(10, EventDropPolicy::DropNewest);
+        let logger = ExposureLogger::new(queue, Duration::from_secs(60), true);
+        let user = User::builder().user_id("u1").build().unwrap();
+        let config = ConfigEvaluationResult {
+            name: "my_config".to_string(),
+            value: serde_json::json!({}),
+            rule_id: Some("rule".to_string()),
+            group_name: None,
+            group: None,
+            secondary_exposures: Vec::new(),
+            reason: EvaluationReason::Network,
+        };
+
+        logger.log_config_exposure(&user, &config);
+        assert!(receiver.try_recv().is_err());
+
+        logger.log_manual_config_exposure(&user, &config);
+        logger.log_manual_config_exposure(&user, &config);
+        let event = receiver.try_recv().unwrap();
+        assert_eq!(event.event_name, CONFIG_EXPOSURE_EVENT);
+        assert_eq!(event.metadata.unwrap()["isManualExposure"], "true");
+        assert!(receiver.try_recv().is_ok());
+    }
+
+    #[tokio::test]
+    async fn layer_exposures_attribute_explicit_parameters() {
+        let (queue, mut receiver) = EventQueue::new(10, EventDropPolicy::DropNewest);
+        let logger = ExposureLogger::new(queue, Duration::from_secs(60), false);
+        let user = User::builder().user_id("u1").build().unwrap();
+        let layer = LayerEvaluationResult {
+            name: "my_layer".to_string(),
+            value: serde_json::json!({"color": "red", "size": 2}),
+            rule_id: Some("rule".to_string()),
+            group_name: None,
+            allocated_experiment_name: Some("my_experiment".to_string()),
+            explicit_parameters: vec!["color".to_string()],
+            secondary_exposures: Vec::new(),
+            undelegated_secondary_exposures: Vec::new(),
+            reason: EvaluationReason::Network,
+        };
+
+        logger.log_layer_exposure(&user, &layer, "color");
+        logger.log_layer_exposure(&user, &layer, "size");
+
+        let explicit = receiver.try_recv().unwrap().metadata.unwrap();
+        assert_eq!(explicit["allocatedExperiment"], "my_experiment");
+        assert_eq!(explicit["isExplicitParameter"], "true");
+        let implicit = receiver.try_recv().unwrap().metadata.unwrap();
+        assert_eq!(implicit["allocatedExperiment"], "");
+        assert_eq!(implicit["parameterName"], "size");
+    }
+}
@@ -35,30 +35,75 @@
 
 pub mod api;
 pub mod batch;
+pub mod bootstrap;
 pub mod cache_metrics;
+pub mod cache_store;
+pub mod circuit_breaker;
 pub mod config;
+pub mod dynamic_config;
 pub mod error;
+mod evaluator;
+pub mod event_logger;
 pub mod events;
+pub mod experiments;
+mod exposures;
+pub mod mock;
+pub mod overrides;
+pub mod rate_limit;
 pub mod response;
-mod transport;
+pub mod specs;
+pub mod transport;
 pub mod user;
 
-use std::collections::HashMap;
+use std::any::{Any, TypeId};
+use std::collections::{HashMap, HashSet};
+use std::future::Future;
 use std::hash::Hash;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
 
+use moka::Expiry;
 use moka::future::Cache;
+use moka::notification::RemovalCause;
+use serde::de::DeserializeOwned;
 use serde_json::Value;
-use tokio::sync::{mpsc, oneshot};
+use tokio::sync::{mpsc, oneshot, watch};
+use tokio::task::JoinHandle;
+use tracing::{info, warn};
 
-pub use api::{ConfigEvaluationResult, GateEvaluationResult, StatsigMetadata};
+use bootstrap::Bootstrap;
+use dynamic_config::deserialize_config;
+use evaluator::Evaluator;
+use exposures::ExposureLogger;
+use overrides::{OverrideFileWatcher, OverrideStore};
+use specs::{SpecStore, SpecSyncer};
+
+pub use api::{
+    CacheEntry, ConfigEvaluationResult, EntityType, EvaluationDetails, EvaluationReason,
+    GateEvaluationResult, LayerEvaluationResult, StatsigMetadata,
+};
 pub use batch::{BatchProcessor, BatchRequest};
-pub use cache_metrics::{CacheMetrics, CacheMetricsSummary};
-pub use config::StatsigClientConfig;
+pub use bootstrap::{BootstrapSnapshot, UserEvaluations};
+pub use cache_metrics::{CacheMetrics, CacheMetricsSummary, EntityCacheStats, EvictionCause};
+pub use cache_store::{
+    CacheStore, EntrySelector, FileCacheStore, MemoryCacheStore, StoredEvaluation, StoredResult,
+};
+pub use circuit_breaker::{CircuitBreakerConfig, CircuitState};
+pub use config::{CacheTtlPolicy, EventDropPolicy, StatsigClientConfig};
+pub use dynamic_config::DynamicConfig;
 pub use error::{Result, StatsigError};
+pub use event_logger::{EventLogger, EventQueue};
 pub use events::{
     ExposureEventMetadata, LogEventResponse, StatsigEvent, StatsigEventTime, StatsigEventValue,
 };
+pub use experiments::{Experiment, Layer};
+pub use mock::MockTransport;
+pub use overrides::{Override, OverrideScope, OverrideValue};
+pub use rate_limit::RateLimitConfig;
 pub use response::ApiResponseHandler;
+pub use specs::{ConfigSpec, ConfigSpecs, SpecCondition, SpecRule};
+pub use transport::{StatsigTransport, Transport};
 pub use user::{EnvironmentTier, StatsigEnvironment, User, UserBuilder};
 
 /// A high-performance, async client for Statsig feature flags and dynamic configs.
@@ -69,6 +114,9 @@ pub use user::{EnvironmentTier, StatsigEnvironment, User, UserBuilder};
 /// - **API Layer**: Handles HTTP communication with Statsig servers
 /// - **Cache Layer**: Provides intelligent caching with TTL support
 /// - **Batch Layer**: Optimizes multiple requests into single API calls
+/// - **Event Layer**: Buffers logged events and flushes them in the background
+/// - **Local Evaluation** (optional): Evaluates gates and configs in-process from
+///   periodically downloaded config specs, with no per-user network calls
 ///
 /// # Performance Characteristics
 ///
@@ -78,11 +126,72 @@ pub use user::{EnvironmentTier, StatsigEnvironment, User, UserBuilder};
 #[derive(Debug)]
 pub struct StatsigClient {
     config: StatsigClientConfig,
-    transport: transport::StatsigTransport,
+    transport: Arc<dyn Transport>,
     cache: Cache<CacheKey, CachedEvaluation>,
-    cache_metrics: CacheMetrics,
+    last_known: Option<Cache<CacheKey, CachedEvaluation>>,
+    cache_metrics: Arc<CacheMetrics>,
+    /// Stale entries with a background refresh in flight
+    refreshing: Arc<std::sync::Mutex<HashSet<CacheKey>>>,
+    /// Cache misses with a fetch in flight, shared with concurrent callers
+    in_flight: std::sync::Mutex<HashMap<CacheKey, watch::Receiver<Option<SharedFetch>>>>,
     batch_sender: mpsc::Sender<BatchRequest>,
-    _shutdown_tx: tokio::sync::broadcast::Sender<()>,
+    event_queue: EventQueue,
+    exposure_logger: ExposureLogger,
+    spec_store: Option<SpecStore>,
+    overrides: OverrideStore,
+    /// Stops the batch processor, which shuts down before the other tasks
+    batch_shutdown_tx: tokio::sync::broadcast::Sender<()>,
+    batch_task: std::sync::Mutex<Option<JoinHandle<()>>>,
+    shutdown_tx: tokio::sync::broadcast::Sender<()>,
+    background_tasks: std::sync::Mutex<Vec<JoinHandle<()>>>,
+    is_shut_down: AtomicBool,
+    /// Bumped by every invalidation so fetches started earlier don't cache
+    /// their results
+    invalidations: Arc<AtomicU64>,
+    /// Evaluations that may still log exposures, awaited during shutdown
+    active_evaluations: ActiveEvaluations,
+    /// Evaluations that failed and were served a default
+    evaluation_errors: AtomicU64,
+}
+
+/// Counts evaluations between their start and their exposure being queued
+#[derive(Debug, Default)]
+struct ActiveEvaluations {
+    count: AtomicUsize,
+    idle: tokio::sync::Notify,
+}
+
+impl ActiveEvaluations {
+    /// Registers an evaluation until the returned guard is dropped
+    ///
+    /// Taken before `ensure_running` so shutdown either rejects the
+    /// evaluation or waits for it.
+    fn track(&self) -> ActiveEvaluation<'_> {
+        self.count.fetch_add(1, Ordering::SeqCst);
+        ActiveEvaluation(self)
+    }
+
+    async fn wait_idle(&self) {
+        loop {
+            let notified = self.idle.notified();
+            tokio::pin!(notified);
+            notified.as_mut().enable();
+            if self.count.load(Ordering::SeqCst) == 0 {
+                return;
+            }
+            notified.await;
+        }
+    }
+}
+
+struct ActiveEvaluation<'a>(&'a ActiveEvaluations);
+
+impl Drop for ActiveEvaluation<'_> {
+    fn drop(&mut self) {
+        if self.0.count.fetch_sub(1, Ordering::SeqCst) == 1 {
+            self.0.idle.notify_waiters();
+        }
+    }
 }
 
 #[derive(Debug, Clone, Hash, Eq, PartialEq)]
@@ -92,22 +201,156 @@ struct CacheKey {
     user_hash: String,
 }
 
-#[derive(Debug, Clone, Hash, Eq, PartialEq)]
-enum EntityType {
-    Gate,
-    Config,
-}
-
 #[derive(Debug, Clone)]
 struct CachedEvaluation {
     result: EvaluationResult,
     timestamp: std::time::Instant,
+    /// Values deserialized from `result` by `get_config_as`, keyed by type
+    typed: Arc<std::sync::Mutex<HashMap<TypeId, Arc<dyn Any + Send + Sync>>>>,
+}
+
+impl CachedEvaluation {
+    fn typed_value<T: Clone + 'static>(&self) -> Option<T> {
+        let typed = self.typed.lock().unwrap_or_else(|e| e.into_inner());
+        typed
+            .get(&TypeId::of::<T>())
+            .and_then(|value| value.downcast_ref::<T>())
+            .cloned()
+    }
+
+    fn store_typed_value<T: Send + Sync + 'static>(&self, value: T) {
+        let mut typed = self.typed.lock().unwrap_or_else(|e| e.into_inner());
+        typed.insert(TypeId::of::<T>(), Arc::new(value));
+    }
+}
+
+/// Expires entries once their TTL from the `CacheTtlPolicy` has elapsed
+/// since they were fetched
+///
+/// Entries warm-loaded from a `CacheStore` keep their original fetch time
+/// rather than getting a fresh TTL.
+struct EvaluationExpiry {
+    ttl: std::time::Duration,
+    policy: CacheTtlPolicy,
+}
+
+impl EvaluationExpiry {
+    fn remaining(&self, key: &CacheKey, value: &CachedEvaluation) -> Option<std::time::Duration> {
+        let ttl = self
+            .policy
+            .ttl_for(&key.entity_type, &key.entity_name, self.ttl)
+            .unwrap_or_default();
+        Some(ttl.saturating_sub(value.timestamp.elapsed()))
+    }
 }
 
+impl Expiry<CacheKey, CachedEvaluation> for EvaluationExpiry {
+    fn expire_after_create(
+        &self,
+        key: &CacheKey,
+        value: &CachedEvaluation,
+        _created_at: std::time::Instant,
+    ) -> Option<std::time::Duration> {
+        self.remaining(key, value)
+    }
+
+    fn expire_after_update(
+        &self,
+        key: &CacheKey,
+        value: &CachedEvaluation,
+        _updated_at: std::time::Instant,
+        _duration_until_expiry: Option<std::time::Duration>,
+    ) -> Option<std::time::Duration> {
+        self.remaining(key, value)
+    }
+}
+
+/// Outcome of a shared fetch; `Ok(None)` if the response omitted the entity
+type SharedFetch = Result<Option<EvaluationResult>>;
+
+/// Removes in-flight entries when the fetching caller finishes or is dropped
+struct InFlightGuard<'a> {
+    in_flight: &'a std::sync::Mutex<HashMap<CacheKey, watch::Receiver<Option<SharedFetch>>>>,
+    entries: Vec<(CacheKey, watch::Receiver<Option<SharedFetch>>)>,
+}
+
+impl Drop for InFlightGuard<'_> {
+    fn drop(&mut self) {
+        let mut in_flight = self.in_flight.lock().unwrap_or_else(|e| e.into_inner());
+        for (key, receiver) in &self.entries {
+            // A new leader may already have replaced an abandoned entry
+            if in_flight
+                .get(key)
+                .is_some_and(|current| current.same_channel(receiver))
+            {
+                in_flight.remove(key);
+            }
+        }
+    }
+}
+
+/// Pending result of a background refresh
+type RefreshResponse = Pin<Box<dyn Future<Output = Option<EvaluationResult>> + Send>>;
+
 #[derive(Debug, Clone)]
 enum EvaluationResult {
     Gate(GateEvaluationResult),
     Config(ConfigEvaluationResult),
+    Layer(LayerEvaluationResult),
+}
+
+impl EvaluationResult {
+    fn name(&self) -> &str {
+        match self {
+            Self::Gate(result) => &result.name,
+            Self::Config(result) => &result.name,
+            Self::Layer(result) => &result.name,
+        }
+    }
+
+    fn entity_type(&self) -> EntityType {
+        match self {
+            Self::Gate(_) => EntityType::Gate,
+            Self::Config(_) => EntityType::Config,
+            Self::Layer(_) => EntityType::Layer,
+        }
+    }
+
+    fn reason(&self) -> EvaluationReason {
+        match self {
+            Self::Gate(result) => result.reason,
+            Self::Config(result) => result.reason,
+            Self::Layer(result) => result.reason,
+        }
+    }
+
+    fn set_reason(&mut self, reason: EvaluationReason) {
+        match self {
+            Self::Gate(result) => result.reason = reason,
+            Self::Config(result) => result.reason = reason,
+            Self::Layer(result) => result.reason = reason,
+        }
+    }
+}
+
+impl From<EvaluationResult> for StoredResult {
+    fn from(result: EvaluationResult) -> Self {
+        match result {
+            EvaluationResult::Gate(result) => Self::Gate(result),
+            EvaluationResult::Config(result) => Self::Config(result),
+            EvaluationResult::Layer(result) => Self::Layer(result),
+        }
+    }
+}
+
+impl From<StoredResult> for EvaluationResult {
+    fn from(result: StoredResult) -> Self {
+        match result {
+            StoredResult::Gate(result) => Self::Gate(result),
+            StoredResult::Config(result) => Self::Config(result),
+            StoredResult::Layer(result) => Self::Layer(result),
+        }
+    }
 }
 
 impl StatsigClient {
@@ -137,45 +380,271 @@ impl StatsigClient {
     /// # Errors
     /// Returns an error if configuration validation fails
     pub async fn with_config(config: StatsigClientConfig) -> Result<Self> {
+        let transport = Self::default_transport(&config)?;
+        Self::with_transport(config, transport).await
+    }
+
+    /// Create a new Statsig client seeded from a bootstrap string
+    ///
+    /// `bootstrap` is either a config spec payload in the
+    /// `/v1/download_config_specs` format, which requires `local_evaluation`,
+    /// or a serialized [`BootstrapSnapshot`] of evaluations for known users.
+    /// Bootstrapped data is served immediately, even if Statsig is
+    /// unreachable; enable `offline_fallback` to keep serving snapshot values
+    /// after they expire from the cache.
+    ///
+    /// # Errors
+    /// Returns `StatsigError::Configuration` if the bootstrap cannot be parsed
+    /// or config validation fails
+    pub async fn with_bootstrap(config: StatsigClientConfig, bootstrap: &str) -> Result<Self> {
+        let bootstrap = Bootstrap::parse(bootstrap)?;
+        let transport = Self::default_transport(&config)?;
+        Self::build(config, transport, Some(bootstrap)).await
+    }
+
+    /// Create a new Statsig client that sends requests through `transport`
+    ///
+    /// Use this with [`MockTransport`] to test code that depends on the
+    /// client without network access.
+    ///
+    /// # Errors
+    /// Returns an error if configuration validation fails
+    pub async fn with_transport(
+        config: StatsigClientConfig,
+        transport: Arc<dyn Transport>,
+    ) -> Result<Self> {
+        Self::build(config, transport, None).await
+    }
+
+    fn default_transport(config: &StatsigClientConfig) -> Result<Arc<dyn Transport>> {
+        if config.local_mode {
+            Ok(Arc::new(transport::LocalModeTransport::new(config)))
+        } else {
+            Ok(Arc::new(StatsigTransport::new(config)?))
+        }
+    }
+
+    async fn build(
+        config: StatsigClientConfig,
+        transport: Arc<dyn Transport>,
+        bootstrap: Option<Bootstrap>,
+    ) -> Result<Self> {
         config.validate()?;
 
-        let transport = transport::StatsigTransport::new(&config)?;
+        let (bootstrap_specs, bootstrap_snapshot) = match bootstrap {
+            Some(Bootstrap::Specs(_)) if !config.local_evaluation => {
+                return Err(StatsigError::configuration(
+                    "Bootstrapping from config specs requires local_evaluation",
+                ));
+            }
+            Some(Bootstrap::Specs(specs)) => (Some(specs), None),
+            Some(Bootstrap::Snapshot(snapshot)) => (None, Some(snapshot)),
+            None => (None, None),
+        };
 
+        let cache_metrics = Arc::new(CacheMetrics::new());
+        let eviction_metrics = cache_metrics.clone();
         let cache = Cache::builder()
-            .time_to_live(config.cache_ttl)
+            .expire_after(EvaluationExpiry {
+                ttl: config.cache_ttl,
+                policy: config.cache_ttl_policy.clone(),
+            })
             .max_capacity(config.cache_max_capacity)
+            .support_invalidation_closures()
+            .eviction_listener(move |_key, _value, cause| {
+                let cause = match cause {
+                    RemovalCause::Expired => EvictionCause::Expired,
+                    RemovalCause::Size => EvictionCause::Size,
+                    RemovalCause::Explicit => EvictionCause::Explicit,
+                    // Overwritten by a fresher evaluation, not evicted
+                    RemovalCause::Replaced => return,
+                };
+                eviction_metrics.record_eviction_cause(cause);
+            })
             .build();
 
+        // Last-known evaluations outlive the TTL so they can be served when offline
+        let last_known = config.offline_fallback.then(|| {
+            Cache::builder()
+                .max_capacity(config.cache_max_capacity)
+                .build()
+        });
+
         let (batch_sender, batch_receiver) = mpsc::channel(1000);
+        let (batch_shutdown_tx, _) = tokio::sync::broadcast::channel(1);
         let (shutdown_tx, _) = tokio::sync::broadcast::channel(1);
 
-        let batch_processor = BatchProcessor::new(batch_receiver, shutdown_tx.subscribe());
-        let _handle = tokio::spawn(batch_processor.run(transport.clone(), config.clone()));
+        let batch_processor = BatchProcessor::new(batch_receiver, batch_shutdown_tx.subscribe());
+        let batch_task = tokio::spawn(batch_processor.run(transport.clone(), config.clone()));
+        let mut background_tasks = Vec::new();
+
+        let (event_queue, event_receiver) =
+            EventQueue::new(config.event_queue_capacity, config.event_drop_policy);
+        let exposure_logger = ExposureLogger::new(
+            event_queue.clone(),
+            config.exposure_dedupe_window,
+            config.exposure_logging_disabled,
+        );
+        let event_logger = EventLogger::new(event_receiver, shutdown_tx.subscribe());
+        background_tasks.push(tokio::spawn(
+            event_logger.run(transport.clone(), config.clone()),
+        ));
+
+        let spec_store = if config.local_evaluation {
+            let store = SpecStore::new();
+            let bootstrapped = bootstrap_specs.is_some();
+            if let Some(specs) = bootstrap_specs {
+                store.set(specs);
+            } else {
+                // Bounded so an unreachable API cannot block client creation
+                let initial_sync =
+                    tokio::time::timeout(config.timeout, store.refresh(transport.as_ref())).await;
+                match initial_sync {
+                    Ok(Ok(_)) => {}
+                    Ok(Err(e)) => warn!(
+                        "Initial config spec download failed, using network evaluation until the next sync: {}",
+                        e
+                    ),
+                    Err(_) => warn!(
+                        "Initial config spec download timed out after {:?}, using network evaluation until the next sync",
+                        config.timeout
+                    ),
+                }
+            }
+            // Bootstrapped specs are served right away and refreshed in the background
+            let syncer = SpecSyncer::new(store.clone(), bootstrapped, shutdown_tx.subscribe());
+            background_tasks.push(tokio::spawn(syncer.run(transport.clone(), config.clone())));
+            Some(store)
+        } else {
+            None
+        };
+
+        let overrides = OverrideStore::new();
+        if let Some(path) = &config.overrides_file {
+            let watcher =
+                OverrideFileWatcher::new(overrides.clone(), path.clone(), shutdown_tx.subscribe());
+            overrides.load_file(path)?;
+            background_tasks.push(tokio::spawn(watcher.run(config.clone())));
+        }
+
+        if let Some(store) = &config.cache_store {
+            warm_load(store.as_ref(), &cache, last_known.as_ref(), &config).await;
+        }
 
-        Ok(Self {
+        let client = Self {
             config,
             transport,
             cache,
-            cache_metrics: CacheMetrics::new(),
+            last_known,
+            cache_metrics,
+            refreshing: Default::default(),
+            in_flight: Default::default(),
             batch_sender,
-            _shutdown_tx: shutdown_tx,
-        })
+            exposure_logger,
+            event_queue,
+            spec_store,
+            overrides,
+            batch_shutdown_tx,
+            batch_task: std::sync::Mutex::new(Some(batch_task)),
+            shutdown_tx,
+            background_tasks: std::sync::Mutex::new(background_tasks),
+            is_shut_down: AtomicBool::new(false),
+            invalidations: Default::default(),
+            active_evaluations: ActiveEvaluations::default(),
+            evaluation_errors: AtomicU64::new(0),
+        };
+
+        if let Some(snapshot) = bootstrap_snapshot {
+            client.seed_cache(snapshot).await;
+        }
+
+        Ok(client)
+    }
+
+    /// Inserts bootstrapped evaluations into the cache
+    async fn seed_cache(&self, snapshot: BootstrapSnapshot) {
+        for evaluations in snapshot.evaluations {
+            let user = &evaluations.user;
+            for mut gate_result in evaluations.gates {
+                gate_result.reason = EvaluationReason::Bootstrap;
+                let cache_key = self.create_cache_key(EntityType::Gate, &gate_result.name, user);
+                self.cache_evaluation(cache_key, EvaluationResult::Gate(gate_result))
+                    .await;
+            }
+            for mut config_result in evaluations.configs {
+                config_result.reason = EvaluationReason::Bootstrap;
+                let cache_key =
+                    self.create_cache_key(EntityType::Config, &config_result.name, user);
+                self.cache_evaluation(cache_key, EvaluationResult::Config(config_result))
+                    .await;
+            }
+            for mut layer_result in evaluations.layers {
+                layer_result.reason = EvaluationReason::Bootstrap;
+                let cache_key = self.create_cache_key(EntityType::Layer, &layer_result.name, user);
+                self.cache_evaluation(cache_key, EvaluationResult::Layer(layer_result))
+                    .await;
+            }
+        }
     }
 
-    pub async fn log_event(&self, event_name: impl Into<String>, user: &User) -> Result<bool> {
+    /// Log a single event for a user
+    ///
+    /// The event is queued and sent in the background by the event logger;
+    /// this never waits on the network.
+    ///
+    /// # Errors
+    /// - `StatsigError::UserValidation` if the user is invalid
+    /// - `StatsigError::EventQueueFull` if the queue is full and the drop
+    ///   policy is `EventDropPolicy::Reject`
+    pub fn log_event(&self, event_name: impl Into<String>, user: &User) -> Result<()> {
         let event = StatsigEvent::builder()
             .event_name(event_name.into())
             .time(StatsigEventTime::UnixMillis(now_ms()))
             .build();
 
-        Ok(self.log_events(vec![event], user).await?.success)
+        self.log_events(vec![event], user)
+    }
+
+    /// Log multiple events for a user
+    ///
+    /// Events are queued and sent in the background. Events without a user
+    /// are attributed to `user`.
+    ///
+    /// # Errors
+    /// Same as `log_event`, plus `StatsigError::Validation` if `events` is empty
+    pub fn log_events(&self, events: Vec<StatsigEvent>, user: &User) -> Result<()> {
+        self.ensure_running()?;
+
+        if events.is_empty() {
+            return Err(StatsigError::validation(
+                "events must contain at least 1 item",
+            ));
+        }
+
+        user.validate_user()
+            .map_err(|e| e.with_context("User validation failed"))?;
+
+        for mut event in events {
+            if event.user.is_none() {
+                event.user = Some(user.clone());
+            }
+            self.event_queue.enqueue(event)?;
+        }
+
+        Ok(())
     }
 
-    pub async fn log_events(
+    /// Send events to the Statsig API immediately, bypassing the event queue
+    ///
+    /// # Errors
+    /// Same as `log_events`, plus any error from the Statsig API
+    pub async fn log_events_immediately(
         &self,
         events: Vec<StatsigEvent>,
         user: &User,
     ) -> Result<LogEventResponse> {
+        self.ensure_running()?;
+
         if events.is_empty() {
             return Err(StatsigError::validation(
                 "events must contain at least 1 item",
@@ -185,14 +654,30 @@ impl StatsigClient {
         user.validate_user()
             .map_err(|e| e.with_context("User validation failed"))?;
 
-        self.transport.log_events(user, &events).await
+        self.transport.log_events(Some(user), &events).await
+    }
+
+    /// Number of events dropped or rejected because the event queue was full
+    pub fn dropped_event_count(&self) -> u64 {
+        self.event_queue.dropped_count()
+    }
+
+    /// Number of evaluations that failed and were served a default by the
+    /// `*_or` and `*_with_details` methods
+    pub fn evaluation_error_count(&self) -> u64 {
+        self.evaluation_errors.load(Ordering::Relaxed)
     }
 
     /// Check if a single feature gate passes for a user
     ///
     /// This method first checks the cache for a recent evaluation, falling back
     /// to the Statsig API if needed. Results are automatically cached for
-    /// the configured TTL duration.
+    /// the configured TTL duration. With `local_evaluation` enabled the gate is
+    /// evaluated in-process from the downloaded config specs instead.
+    ///
+    /// A `statsig::gate_exposure` event is logged for every evaluation served,
+    /// deduplicated per user, gate, rule and value within
+    /// `exposure_dedupe_window`.
     ///
     /// # Arguments
     ///
@@ -207,7 +692,8 @@ impl StatsigClient {
     /// # Errors
     ///
     /// - `StatsigError::Validation` if the gate name or user is invalid
-    /// - `StatsigError::Network` if the API request fails
+    /// - `StatsigError::Network` if the API request fails and `offline_fallback`
+    ///   is disabled
     /// - `StatsigError::Api` if the server returns an error response
     ///
     /// # Performance
@@ -220,6 +706,69 @@ impl StatsigClient {
         Ok(results.into_values().next().unwrap_or(false))
     }
 
+    /// Check a feature gate and report where the result came from
+    ///
+    /// Never fails: if evaluation errors, the configured
+    /// `offline_default_gate_value` is returned with reason
+    /// `EvaluationReason::Error` and the error attached. Exposures are logged
+    /// as for `check_gate`.
+    pub async fn check_gate_with_details(
+        &self,
+        gate_name: impl Into<String>,
+        user: &User,
+    ) -> EvaluationDetails<bool> {
+        let _active = self.active_evaluations.track();
+        let gate_name = gate_name.into();
+        let evaluation = self
+            .evaluate_gates(vec![gate_name.clone()], user)
+            .await
+            .and_then(|mut results| {
+                results
+                    .remove(&gate_name)
+                    .ok_or_else(|| StatsigError::gate_not_found(&gate_name))
+            });
+
+        match evaluation {
+            Ok(gate_result) => {
+                self.exposure_logger.log_gate_exposure(user, &gate_result);
+                let cache_key = self.create_cache_key(EntityType::Gate, &gate_name, user);
+                self.evaluation_details(
+                    &cache_key,
+                    gate_result.value,
+                    gate_result.reason,
+                    gate_result.rule_id,
+                    gate_result.group_name,
+                )
+                .await
+            }
+            Err(e) => {
+                warn!("Failed to evaluate gate {}: {}", gate_name, e);
+                self.evaluation_errors.fetch_add(1, Ordering::Relaxed);
+                EvaluationDetails::from_error(self.config.offline_default_gate_value, e)
+            }
+        }
+    }
+
+    /// Check a feature gate, returning `default` if no real value is available
+    ///
+    /// `default` replaces both failed evaluations and the configured
+    /// `offline_default_gate_value`; last-known values served by
+    /// `offline_fallback` are kept. Never fails: errors are logged and counted
+    /// in `evaluation_error_count`.
+    pub async fn check_gate_or(
+        &self,
+        gate_name: impl Into<String>,
+        user: &User,
+        default: bool,
+    ) -> bool {
+        let details = self.check_gate_with_details(gate_name, user).await;
+        if details.reason.is_default() {
+            default
+        } else {
+            details.value
+        }
+    }
+
     /// Check multiple feature gates for a user
     ///
     /// This method efficiently checks multiple gates in a single API call when
@@ -240,53 +789,53 @@ impl StatsigClient {
         gate_names: Vec<String>,
         user: &User,
     ) -> Result<HashMap<String, bool>> {
-        if gate_names.is_empty() {
-            return Ok(HashMap::new());
-        }
-
-        for gate_name in &gate_names {
-            validate_entity_name("gate", gate_name)?;
-        }
-
-        user.validate_user()
-            .map_err(|e| e.with_context("User validation failed"))?;
-
-        let mut results = HashMap::new();
-        let mut missing_gates = Vec::new();
-
-        // Check cache first
-        for gate_name in &gate_names {
-            let cache_key = self.create_cache_key(EntityType::Gate, gate_name, user);
-            if let Some(cached) = self.cache.get(&cache_key).await {
-                self.cache_metrics.record_hit();
-                if let EvaluationResult::Gate(gate_result) = cached.result {
-                    results.insert(gate_name.clone(), gate_result.value);
-                }
-            } else {
-                self.cache_metrics.record_miss();
-                missing_gates.push(gate_name.clone());
-            }
+        let _active = self.active_evaluations.track();
+        let evaluations = self.evaluate_gates(gate_names, user).await?;
+        for gate_result in evaluations.values() {
+            self.exposure_logger.log_gate_exposure(user, gate_result);
         }
 
-        if missing_gates.is_empty() {
-            return Ok(results);
-        }
+        Ok(evaluations
+            .into_iter()
+            .map(|(name, gate_result)| (name, gate_result.value))
+            .collect())
+    }
 
-        // Fetch missing gates from API
-        let gate_results = self.fetch_gates_batch(missing_gates, user).await?;
+    /// Check a feature gate without logging an exposure
+    ///
+    /// Use together with [`StatsigClient::manually_log_gate_exposure`] when the
+    /// gated feature is only shown to the user later, if at all.
+    pub async fn check_gate_with_exposure_logging_disabled(
+        &self,
+        gate_name: impl Into<String>,
+        user: &User,
+    ) -> Result<bool> {
+        let gate_name = gate_name.into();
+        let results = self.evaluate_gates(vec![gate_name], user).await?;
+        Ok(results
+            .into_values()
+            .next()
+            .map(|gate_result| gate_result.value)
+            .unwrap_or(false))
+    }
 
-        for gate_result in gate_results {
-            let cache_key = self.create_cache_key(EntityType::Gate, &gate_result.name, user);
-            let cached = CachedEvaluation {
-                result: EvaluationResult::Gate(gate_result.clone()),
-                timestamp: std::time::Instant::now(),
-            };
-            self.cache_metrics.record_insert();
-            self.cache.insert(cache_key, cached).await;
-            results.insert(gate_result.name, gate_result.value);
+    /// Log an exposure for a feature gate checked with exposure logging disabled
+    ///
+    /// The gate is re-evaluated (normally from cache) and the exposure is
+    /// logged with `isManualExposure` set, bypassing deduplication.
+    pub async fn manually_log_gate_exposure(
+        &self,
+        gate_name: impl Into<String>,
+        user: &User,
+    ) -> Result<()> {
+        let _active = self.active_evaluations.track();
+        let gate_name = gate_name.into();
+        let results = self.evaluate_gates(vec![gate_name], user).await?;
+        for gate_result in results.values() {
+            self.exposure_logger
+                .log_manual_gate_exposure(user, gate_result);
         }
-
-        Ok(results)
+        Ok(())
     }
 
     /// Get a single dynamic config for a user
@@ -328,6 +877,149 @@ impl StatsigClient {
             .ok_or_else(|| StatsigError::internal("Missing config evaluation in response"))
     }
 
+    /// Get a dynamic config and report where the value came from
+    ///
+    /// Never fails: if evaluation errors, the configured
+    /// `offline_default_config_value` is returned with reason
+    /// `EvaluationReason::Error` and the error attached. Exposures are logged
+    /// as for `get_config`.
+    pub async fn get_config_with_details(
+        &self,
+        config_name: impl Into<String>,
+        user: &User,
+    ) -> EvaluationDetails<Value> {
+        let _active = self.active_evaluations.track();
+        let config_name = config_name.into();
+        let evaluation = self
+            .evaluate_configs(vec![config_name.clone()], user)
+            .await
+            .and_then(|mut results| {
+                results
+                    .remove(&config_name)
+                    .ok_or_else(|| StatsigError::config_not_found(&config_name))
+            });
+
+        match evaluation {
+            Ok(config_result) => {
+                self.exposure_logger
+                    .log_config_exposure(user, &config_result);
+                let cache_key = self.create_cache_key(EntityType::Config, &config_name, user);
+                self.evaluation_details(
+                    &cache_key,
+                    config_result.value,
+                    config_result.reason,
+                    config_result.rule_id,
+                    config_result.group_name,
+                )
+                .await
+            }
+            Err(e) => {
+                warn!("Failed to evaluate config {}: {}", config_name, e);
+                self.evaluation_errors.fetch_add(1, Ordering::Relaxed);
+                EvaluationDetails::from_error(self.config.offline_default_config_value.clone(), e)
+            }
+        }
+    }
+
+    /// Get a dynamic config, returning `default` if no real value is available
+    ///
+    /// `default` replaces both failed evaluations and the configured
+    /// `offline_default_config_value`; last-known values served by
+    /// `offline_fallback` are kept. Never fails: errors are logged and counted
+    /// in `evaluation_error_count`.
+    pub async fn get_config_or(
+        &self,
+        config_name: impl Into<String>,
+        user: &User,
+        default: Value,
+    ) -> Value {
+        let details = self.get_config_with_details(config_name, user).await;
+        if details.reason.is_default() {
+            default
+        } else {
+            details.value
+        }
+    }
+
+    /// Get a dynamic config with typed parameter accessors
+    pub async fn get_dynamic_config(
+        &self,
+        config_name: impl Into<String>,
+        user: &User,
+    ) -> Result<DynamicConfig> {
+        let evaluation = self.get_config_evaluation(config_name, user).await?;
+        Ok(DynamicConfig::from(evaluation))
+    }
+
+    /// Get a dynamic config deserialized into `T`
+    ///
+    /// The deserialized value is cached alongside the raw evaluation, so
+    /// repeated calls for the same user and type skip deserialization.
+    ///
+    /// # Errors
+    /// Returns `StatsigError::ConfigDeserialization` with the config name and
+    /// the JSON path that failed when the config value does not match `T`
+    pub async fn get_config_as<T>(&self, config_name: impl Into<String>, user: &User) -> Result<T>
+    where
+        T: DeserializeOwned + Clone + Send + Sync + 'static,
+    {
+        let config_name = config_name.into();
+        let evaluation = self
+            .get_config_evaluation(config_name.clone(), user)
+            .await?;
+
+        // Only network and cache results are backed by a cache entry
+        let cached = match evaluation.reason {
+            EvaluationReason::Network | EvaluationReason::Cache => {
+                let cache_key = self.create_cache_key(EntityType::Config, &config_name, user);
+                self.cache.get(&cache_key).await
+            }
+            _ => None,
+        };
+        if let Some(value) = cached.as_ref().and_then(CachedEvaluation::typed_value::<T>) {
+            return Ok(value);
+        }
+
+        let value: T = deserialize_config(&config_name, None, &evaluation.value)?;
+        if let Some(cached) = cached {
+            cached.store_typed_value(value.clone());
+        }
+        Ok(value)
+    }
+
+    /// Get a dynamic config without logging an exposure
+    ///
+    /// Use together with [`StatsigClient::manually_log_config_exposure`].
+    pub async fn get_config_with_exposure_logging_disabled(
+        &self,
+        config_name: impl Into<String>,
+        user: &User,
+    ) -> Result<Value> {
+        let config_name = config_name.into();
+        let results = self.evaluate_configs(vec![config_name], user).await?;
+        Ok(results
+            .into_values()
+            .next()
+            .map(|config_result| config_result.value)
+            .unwrap_or(Value::Null))
+    }
+
+    /// Log an exposure for a config fetched with exposure logging disabled
+    pub async fn manually_log_config_exposure(
+        &self,
+        config_name: impl Into<String>,
+        user: &User,
+    ) -> Result<()> {
+        let _active = self.active_evaluations.track();
+        let config_name = config_name.into();
+        let results = self.evaluate_configs(vec![config_name], user).await?;
+        for config_result in results.values() {
+            self.exposure_logger
+                .log_manual_config_exposure(user, config_result);
+        }
+        Ok(())
+    }
+
     /// Get multiple dynamic configs for a user
     ///
     /// Efficiently retrieves multiple configuration objects (or experiments) in parallel when
@@ -360,30 +1052,300 @@ impl StatsigClient {
         config_names: Vec<String>,
         user: &User,
     ) -> Result<HashMap<String, ConfigEvaluationResult>> {
-        if config_names.is_empty() {
-            return Ok(HashMap::new());
+        let _active = self.active_evaluations.track();
+        let evaluations = self.evaluate_configs(config_names, user).await?;
+        for config_result in evaluations.values() {
+            self.exposure_logger
+                .log_config_exposure(user, config_result);
         }
 
-        for config_name in &config_names {
-            validate_entity_name("config", config_name)?;
-        }
-
-        user.validate_user()
-            .map_err(|e| e.with_context("User validation failed"))?;
+        Ok(evaluations)
+    }
 
-        let mut results = HashMap::new();
+    /// Get an experiment evaluation for a user
+    ///
+    /// Experiments are served through the config pipeline, so a config exposure
+    /// is logged for the evaluation.
+    pub async fn get_experiment(
+        &self,
+        experiment_name: impl Into<String>,
+        user: &User,
+    ) -> Result<Experiment> {
+        let evaluation = self.get_config_evaluation(experiment_name, user).await?;
+        Ok(Experiment::from(evaluation))
+    }
+
+    /// Get a layer evaluation for a user
+    ///
+    /// No exposure is logged here. Reading a parameter with [`Layer::get`]
+    /// logs a layer exposure for that parameter.
+    pub async fn get_layer(&self, layer_name: impl Into<String>, user: &User) -> Result<Layer> {
+        let evaluation = self.evaluate_layer(layer_name.into(), user).await?;
+        Ok(Layer::new(
+            evaluation,
+            user.clone(),
+            Some(self.exposure_logger.clone()),
+        ))
+    }
+
+    /// Override a gate for every user, or only for `user_id`
+    ///
+    /// Overridden gates are served before local evaluation, the cache and the
+    /// network, with `EvaluationReason::Override`, and no exposure is logged.
+    pub fn override_gate(&self, gate_name: impl Into<String>, value: bool, user_id: Option<&str>) {
+        self.override_gate_with_scope(gate_name, value, OverrideScope::from_user_id(user_id));
+    }
+
+    /// Override a gate for the users matching `scope`
+    pub fn override_gate_with_scope(
+        &self,
+        gate_name: impl Into<String>,
+        value: bool,
+        scope: OverrideScope,
+    ) {
+        self.overrides.set_gate(gate_name.into(), scope, value);
+    }
+
+    /// Override a dynamic config for every user, or only for `user_id`
+    pub fn override_config(
+        &self,
+        config_name: impl Into<String>,
+        value: Value,
+        user_id: Option<&str>,
+    ) {
+        self.override_config_with_scope(config_name, value, OverrideScope::from_user_id(user_id));
+    }
+
+    /// Override a dynamic config for the users matching `scope`
+    pub fn override_config_with_scope(
+        &self,
+        config_name: impl Into<String>,
+        value: Value,
+        scope: OverrideScope,
+    ) {
+        self.overrides.set_config(config_name.into(), scope, value);
+    }
+
+    /// Remove a gate override, returning `true` if one existed for `scope`
+    pub fn remove_gate_override(&self, gate_name: &str, scope: &OverrideScope) -> bool {
+        self.overrides.remove_gate(gate_name, scope)
+    }
+
+    /// Remove a config override, returning `true` if one existed for `scope`
+    pub fn remove_config_override(&self, config_name: &str, scope: &OverrideScope) -> bool {
+        self.overrides.remove_config(config_name, scope)
+    }
+
+    /// Remove every override set through the client
+    pub fn clear_overrides(&self) {
+        self.overrides.clear();
+    }
+
+    /// List all overrides set through the client
+    ///
+    /// Overrides loaded from `overrides_file` are not included.
+    pub fn list_overrides(&self) -> Vec<Override> {
+        self.overrides.list()
+    }
+
+    /// Error from the last failed reload of `overrides_file`, if any
+    ///
+    /// Cleared once the file parses again. While set, the last good set of
+    /// file overrides stays in effect.
+    pub fn overrides_file_error(&self) -> Option<StatsigError> {
+        self.overrides.file_error()
+    }
+
+    /// Evaluates gates without logging exposures
+    async fn evaluate_gates(
+        &self,
+        gate_names: Vec<String>,
+        user: &User,
+    ) -> Result<HashMap<String, GateEvaluationResult>> {
+        self.ensure_running()?;
+
+        if gate_names.is_empty() {
+            return Ok(HashMap::new());
+        }
+
+        for gate_name in &gate_names {
+            validate_entity_name("gate", gate_name)?;
+        }
+
+        user.validate_user()
+            .map_err(|e| e.with_context("User validation failed"))?;
+
+        let mut results = HashMap::new();
+        let mut missing_gates = Vec::new();
+
+        // Overrides take precedence over every other source
+        let gate_names: Vec<String> = gate_names
+            .into_iter()
+            .filter(|gate_name| match self.overrides.gate(gate_name, user) {
+                Some(value) => {
+                    results.insert(
+                        gate_name.clone(),
+                        GateEvaluationResult {
+                            name: gate_name.clone(),
+                            value,
+                            rule_id: None,
+                            group_name: None,
+                            secondary_exposures: Vec::new(),
+                            reason: EvaluationReason::Override,
+                        },
+                    );
+                    false
+                }
+                None => true,
+            })
+            .collect();
+
+        // Evaluate locally when config specs are available
+        let gate_names = match self.spec_store.as_ref().and_then(SpecStore::get) {
+            Some(specs) => {
+                let evaluator = Evaluator::new(&specs);
+                let mut remaining = Vec::new();
+                for gate_name in gate_names {
+                    match evaluator.check_gate(user, &gate_name) {
+                        Some(gate_result) => {
+                            results.insert(gate_name, gate_result);
+                        }
+                        None => remaining.push(gate_name),
+                    }
+                }
+                remaining
+            }
+            None => gate_names,
+        };
+
+        // Check cache first
+        for gate_name in &gate_names {
+            let cache_key = self.create_cache_key(EntityType::Gate, gate_name, user);
+            if let Some(cached) = self.cache.get(&cache_key).await {
+                self.cache_metrics.record_entity_hit(&cache_key.entity_type);
+                self.refresh_if_stale(&cache_key, &cached, user);
+                if let EvaluationResult::Gate(mut gate_result) = cached.result {
+                    gate_result.reason = gate_result.reason.served_from_cache();
+                    results.insert(gate_name.clone(), gate_result);
+                }
+            } else {
+                self.cache_metrics
+                    .record_entity_miss(&cache_key.entity_type);
+                missing_gates.push(gate_name.clone());
+            }
+        }
+
+        if missing_gates.is_empty() {
+            return Ok(results);
+        }
+
+        // Fetch missing gates from API
+        let gate_results = match self
+            .fetch_coalesced(EntityType::Gate, missing_gates.clone(), user)
+            .await
+        {
+            Ok(gate_results) => gate_results,
+            Err(e) if self.config.offline_fallback && e.is_unavailable() => {
+                warn!(
+                    "Statsig API unavailable, serving fallback gate values: {}",
+                    e
+                );
+                for gate_name in missing_gates {
+                    let gate_result = self.gate_fallback(&gate_name, user).await;
+                    results.insert(gate_name, gate_result);
+                }
+                return Ok(results);
+            }
+            Err(e) => return Err(e),
+        };
+
+        for gate_result in gate_results {
+            if let EvaluationResult::Gate(gate_result) = gate_result {
+                results.insert(gate_result.name.clone(), gate_result);
+            }
+        }
+
+        Ok(results)
+    }
+
+    /// Evaluates configs without logging exposures
+    async fn evaluate_configs(
+        &self,
+        config_names: Vec<String>,
+        user: &User,
+    ) -> Result<HashMap<String, ConfigEvaluationResult>> {
+        self.ensure_running()?;
+
+        if config_names.is_empty() {
+            return Ok(HashMap::new());
+        }
+
+        for config_name in &config_names {
+            validate_entity_name("config", config_name)?;
+        }
+
+        user.validate_user()
+            .map_err(|e| e.with_context("User validation failed"))?;
+
+        let mut results = HashMap::new();
         let mut missing_configs = Vec::new();
 
+        // Overrides take precedence over every other source
+        let config_names: Vec<String> = config_names
+            .into_iter()
+            .filter(
+                |config_name| match self.overrides.config(config_name, user) {
+                    Some(value) => {
+                        results.insert(
+                            config_name.clone(),
+                            ConfigEvaluationResult {
+                                name: config_name.clone(),
+                                value,
+                                rule_id: None,
+                                group_name: None,
+                                group: None,
+                                secondary_exposures: Vec::new(),
+                                reason: EvaluationReason::Override,
+                            },
+                        );
+                        false
+                    }
+                    None => true,
+                },
+            )
+            .collect();
+
+        // Evaluate locally when config specs are available
+        let config_names = match self.spec_store.as_ref().and_then(SpecStore::get) {
+            Some(specs) => {
+                let evaluator = Evaluator::new(&specs);
+                let mut remaining = Vec::new();
+                for config_name in config_names {
+                    match evaluator.get_config(user, &config_name) {
+                        Some(config_result) => {
+                            results.insert(config_name, config_result);
+                        }
+                        None => remaining.push(config_name),
+                    }
+                }
+                remaining
+            }
+            None => config_names,
+        };
+
         // Check cache first
         for config_name in &config_names {
             let cache_key = self.create_cache_key(EntityType::Config, config_name, user);
             if let Some(cached) = self.cache.get(&cache_key).await {
-                self.cache_metrics.record_hit();
-                if let EvaluationResult::Config(config_result) = cached.result {
+                self.cache_metrics.record_entity_hit(&cache_key.entity_type);
+                self.refresh_if_stale(&cache_key, &cached, user);
+                if let EvaluationResult::Config(mut config_result) = cached.result {
+                    config_result.reason = config_result.reason.served_from_cache();
                     results.insert(config_name.clone(), config_result);
                 }
             } else {
-                self.cache_metrics.record_miss();
+                self.cache_metrics
+                    .record_entity_miss(&cache_key.entity_type);
                 missing_configs.push(config_name.clone());
             }
         }
@@ -393,22 +1355,137 @@ impl StatsigClient {
         }
 
         // Fetch missing configs from API
-        let config_results = self.fetch_configs_batch(missing_configs, user).await?;
+        let config_results = match self
+            .fetch_coalesced(EntityType::Config, missing_configs.clone(), user)
+            .await
+        {
+            Ok(config_results) => config_results,
+            Err(e) if self.config.offline_fallback && e.is_unavailable() => {
+                warn!(
+                    "Statsig API unavailable, serving fallback config values: {}",
+                    e
+                );
+                for config_name in missing_configs {
+                    let config_result = self.config_fallback(&config_name, user).await;
+                    results.insert(config_name, config_result);
+                }
+                return Ok(results);
+            }
+            Err(e) => return Err(e),
+        };
 
         for config_result in config_results {
-            let cache_key = self.create_cache_key(EntityType::Config, &config_result.name, user);
-            let cached = CachedEvaluation {
-                result: EvaluationResult::Config(config_result.clone()),
-                timestamp: std::time::Instant::now(),
-            };
-            self.cache_metrics.record_insert();
-            self.cache.insert(cache_key, cached).await;
-            results.insert(config_result.name.clone(), config_result);
+            if let EvaluationResult::Config(config_result) = config_result {
+                results.insert(config_result.name.clone(), config_result);
+            }
         }
 
         Ok(results)
     }
 
+    /// Evaluates a layer without logging exposures
+    async fn evaluate_layer(
+        &self,
+        layer_name: String,
+        user: &User,
+    ) -> Result<LayerEvaluationResult> {
+        self.ensure_running()?;
+
+        validate_entity_name("layer", &layer_name)?;
+
+        user.validate_user()
+            .map_err(|e| e.with_context("User validation failed"))?;
+
+        // Evaluate locally when config specs are available
+        if let Some(specs) = self.spec_store.as_ref().and_then(SpecStore::get) {
+            if let Some(layer_result) = Evaluator::new(&specs).get_layer(user, &layer_name) {
+                return Ok(layer_result);
+            }
+        }
+
+        // Check cache first
+        let cache_key = self.create_cache_key(EntityType::Layer, &layer_name, user);
+        if let Some(cached) = self.cache.get(&cache_key).await {
+            self.cache_metrics.record_entity_hit(&cache_key.entity_type);
+            self.refresh_if_stale(&cache_key, &cached, user);
+            if let EvaluationResult::Layer(mut layer_result) = cached.result {
+                layer_result.reason = layer_result.reason.served_from_cache();
+                return Ok(layer_result);
+            }
+        } else {
+            self.cache_metrics
+                .record_entity_miss(&cache_key.entity_type);
+        }
+
+        // Fetch from API
+        let layer_result = match self
+            .fetch_coalesced(EntityType::Layer, vec![layer_name.clone()], user)
+            .await
+        {
+            Ok(layer_results) => match layer_results.into_iter().next() {
+                Some(EvaluationResult::Layer(layer_result)) => layer_result,
+                _ => {
+                    return Err(StatsigError::internal(
+                        "Missing layer evaluation in response",
+                    ));
+                }
+            },
+            Err(e) if self.config.offline_fallback && e.is_unavailable() => {
+                warn!(
+                    "Statsig API unavailable, serving fallback layer values: {}",
+                    e
+                );
+                return Ok(self.layer_fallback(&layer_name, user).await);
+            }
+            Err(e) => return Err(e),
+        };
+
+        Ok(layer_result)
+    }
+
+    /// Builds details for a successful evaluation, looking up its cache age
+    /// and definition version
+    async fn evaluation_details<T>(
+        &self,
+        cache_key: &CacheKey,
+        value: T,
+        reason: EvaluationReason,
+        rule_id: Option<String>,
+        group_name: Option<String>,
+    ) -> EvaluationDetails<T> {
+        let cache_age = match reason {
+            EvaluationReason::Cache | EvaluationReason::Bootstrap => self
+                .cache
+                .get(cache_key)
+                .await
+                .map(|cached| cached.timestamp.elapsed()),
+            _ => None,
+        };
+        let config_version = self.spec_store.as_ref().and_then(|store| {
+            let specs = store.get()?;
+            let specs = match cache_key.entity_type {
+                EntityType::Gate => &specs.gates,
+                EntityType::Config => &specs.configs,
+                EntityType::Layer => &specs.layers,
+            };
+            specs.get(&cache_key.entity_name)?.version
+        });
+        let now = std::time::SystemTime::now();
+
+        EvaluationDetails {
+            value,
+            reason,
+            rule_id,
+            group_name,
+            config_version,
+            evaluation_time: cache_age
+                .and_then(|age| now.checked_sub(age))
+                .unwrap_or(now),
+            cache_age,
+            error: None,
+        }
+    }
+
     fn create_cache_key(
         &self,
         entity_type: EntityType,
@@ -423,6 +1500,397 @@ impl StatsigClient {
         }
     }
 
+    async fn cache_evaluation(&self, cache_key: CacheKey, result: EvaluationResult) {
+        // Never-cache entities are only remembered for offline fallback
+        let cacheable = self
+            .config
+            .cache_ttl_for(&cache_key.entity_type, &cache_key.entity_name)
+            .is_some();
+        store_evaluation(
+            cacheable.then_some(&self.cache),
+            self.last_known.as_ref(),
+            self.config.cache_store.as_ref().filter(|_| cacheable),
+            &self.cache_metrics,
+            cache_key,
+            result,
+        )
+        .await;
+    }
+
+    /// Refreshes a cache entry older than `cache_soft_ttl` in the background
+    ///
+    /// The stale entry keeps being served until the refresh lands or the entry
+    /// reaches `cache_ttl`. Failed refreshes are retried on the next hit.
+    fn refresh_if_stale(&self, cache_key: &CacheKey, cached: &CachedEvaluation, user: &User) {
+        let Some(soft_ttl) = self.config.cache_soft_ttl else {
+            return;
+        };
+        // Entries with a TTL at or below the soft TTL expire before going
+        // stale and are simply refetched
+        let ttl = self
+            .config
+            .cache_ttl_for(&cache_key.entity_type, &cache_key.entity_name);
+        if ttl.is_none_or(|ttl| ttl <= soft_ttl) || cached.timestamp.elapsed() < soft_ttl {
+            return;
+        }
+
+        // One refresh per entry at a time
+        if !self
+            .refreshing
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(cache_key.clone())
+        {
+            return;
+        }
+
+        let generation = self.invalidations.load(Ordering::SeqCst);
+        let names = vec![cache_key.entity_name.clone()];
+        let (request, response): (BatchRequest, RefreshResponse) = match cache_key.entity_type {
+            EntityType::Gate => {
+                let (response_tx, response_rx) = oneshot::channel();
+                let request = BatchRequest::CheckGates {
+                    gate_names: names,
+                    user: user.clone(),
+                    response_tx,
+                };
+                let response = async move {
+                    let gate_result = response_rx.await.ok()?.ok()?.into_iter().next()?;
+                    Some(EvaluationResult::Gate(gate_result))
+                };
+                (request, Box::pin(response))
+            }
+            EntityType::Config => {
+                let (response_tx, response_rx) = oneshot::channel();
+                let request = BatchRequest::GetConfigs {
+                    config_names: names,
+                    user: user.clone(),
+                    response_tx,
+                };
+                let response = async move {
+                    let config_result = response_rx.await.ok()?.into_iter().next()?.ok()?;
+                    Some(EvaluationResult::Config(config_result))
+                };
+                (request, Box::pin(response))
+            }
+            EntityType::Layer => {
+                let (response_tx, response_rx) = oneshot::channel();
+                let request = BatchRequest::GetLayers {
+                    layer_names: names,
+                    user: user.clone(),
+                    response_tx,
+                };
+                let response = async move {
+                    let layer_result = response_rx.await.ok()?.into_iter().next()?.ok()?;
+                    Some(EvaluationResult::Layer(layer_result))
+                };
+                (request, Box::pin(response))
+            }
+        };
+
+        let cache_key = cache_key.clone();
+        let refreshing = self.refreshing.clone();
+        // Never block the caller on a full batch queue; the next hit retries
+        if self.batch_sender.try_send(request).is_err() {
+            refreshing
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .remove(&cache_key);
+            return;
+        }
+
+        let cache = self.cache.clone();
+        let last_known = self.last_known.clone();
+        let cache_store = self.config.cache_store.clone();
+        let cache_metrics = self.cache_metrics.clone();
+        let invalidations = self.invalidations.clone();
+        tokio::spawn(async move {
+            match response.await {
+                // Invalidated while in flight; the next miss fetches afresh
+                Some(_) if invalidations.load(Ordering::SeqCst) != generation => {}
+                Some(result) => {
+                    store_evaluation(
+                        Some(&cache),
+                        last_known.as_ref(),
+                        cache_store.as_ref(),
+                        &cache_metrics,
+                        cache_key.clone(),
+                        result,
+                    )
+                    .await
+                }
+                None => warn!(
+                    "Background refresh failed for {}, serving stale value",
+                    cache_key.entity_name
+                ),
+            }
+            refreshing
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .remove(&cache_key);
+        });
+    }
+
+    /// Serves the last-known gate evaluation, or the configured default
+    async fn gate_fallback(&self, gate_name: &str, user: &User) -> GateEvaluationResult {
+        let cache_key = self.create_cache_key(EntityType::Gate, gate_name, user);
+        if let Some(last_known) = &self.last_known {
+            if let Some(cached) = last_known.get(&cache_key).await {
+                if let EvaluationResult::Gate(mut gate_result) = cached.result {
+                    gate_result.reason = EvaluationReason::Fallback;
+                    return gate_result;
+                }
+            }
+        }
+
+        GateEvaluationResult {
+            name: gate_name.to_string(),
+            value: self.config.offline_default_gate_value,
+            rule_id: None,
+            group_name: None,
+            secondary_exposures: Vec::new(),
+            reason: EvaluationReason::Default,
+        }
+    }
+
+    /// Serves the last-known config evaluation, or the configured default
+    async fn config_fallback(&self, config_name: &str, user: &User) -> ConfigEvaluationResult {
+        let cache_key = self.create_cache_key(EntityType::Config, config_name, user);
+        if let Some(last_known) = &self.last_known {
+            if let Some(cached) = last_known.get(&cache_key).await {
+                if let EvaluationResult::Config(mut config_result) = cached.result {
+                    config_result.reason = EvaluationReason::Fallback;
+                    return config_result;
+                }
+            }
+        }
+
+        ConfigEvaluationResult {
+            name: config_name.to_string(),
+            value: self.config.offline_default_config_value.clone(),
+            rule_id: None,
+            group_name: None,
+            group: None,
+            secondary_exposures: Vec::new(),
+            reason: EvaluationReason::Default,
+        }
+    }
+
+    /// Serves the last-known layer evaluation, or an empty layer
+    async fn layer_fallback(&self, layer_name: &str, user: &User) -> LayerEvaluationResult {
+        let cache_key = self.create_cache_key(EntityType::Layer, layer_name, user);
+        if let Some(last_known) = &self.last_known {
+            if let Some(cached) = last_known.get(&cache_key).await {
+                if let EvaluationResult::Layer(mut layer_result) = cached.result {
+                    layer_result.reason = EvaluationReason::Fallback;
+                    return layer_result;
+                }
+            }
+        }
+
+        LayerEvaluationResult {
+            name: layer_name.to_string(),
+            value: Value::Object(Default::default()),
+            rule_id: None,
+            group_name: None,
+            allocated_experiment_name: None,
+            explicit_parameters: Vec::new(),
+            secondary_exposures: Vec::new(),
+            undelegated_secondary_exposures: Vec::new(),
+            reason: EvaluationReason::Default,
+        }
+    }
+
+    /// Fetches and caches missing entities, sharing fetches between concurrent callers
+    ///
+    /// The first caller to miss on an (entity, user) pair fetches it; callers
+    /// that miss on the same pair while that fetch is in flight wait for its
+    /// result instead of issuing their own request. If the fetching caller is
+    /// cancelled, its waiters fetch again themselves.
+    async fn fetch_coalesced(
+        &self,
+        entity_type: EntityType,
+        names: Vec<String>,
+        user: &User,
+    ) -> Result<Vec<EvaluationResult>> {
+        let generation = self.invalidations.load(Ordering::SeqCst);
+        let mut leading = Vec::new();
+        let mut following = Vec::new();
+        {
+            let mut in_flight = self.in_flight.lock().unwrap_or_else(|e| e.into_inner());
+            for name in names {
+                let cache_key = self.create_cache_key(entity_type.clone(), &name, user);
+                match in_flight.get(&cache_key) {
+                    // A closed channel belongs to a leader that was dropped
+                    Some(receiver) if receiver.has_changed().is_ok() => {
+                        following.push((name, receiver.clone()))
+                    }
+                    _ => {
+                        let (sender, receiver) = watch::channel(None);
+                        in_flight.insert(cache_key.clone(), receiver);
+                        leading.push((name, cache_key, sender));
+                    }
+                }
+            }
+        }
+        // Releases leadership even if this future is dropped mid-fetch
+        let _leadership = InFlightGuard {
+            in_flight: &self.in_flight,
+            entries: leading
+                .iter()
+                .map(|(_, key, sender)| (key.clone(), sender.subscribe()))
+                .collect(),
+        };
+
+        let mut results = Vec::new();
+        let mut remaining = Vec::new();
+        for (name, cache_key, sender) in leading {
+            match self.read_through(&cache_key, generation).await {
+                Some(result) => {
+                    results.push(result.clone());
+                    sender.send_replace(Some(Ok(Some(result))));
+                }
+                None => remaining.push((name, cache_key, sender)),
+            }
+        }
+        let leading = remaining;
+
+        if !leading.is_empty() {
+            let names: Vec<String> = leading.iter().map(|(name, _, _)| name.clone()).collect();
+            // Configs and layers are fetched one by one and fail individually
+            let fetched: Result<HashMap<String, Result<EvaluationResult>>> = match entity_type {
+                EntityType::Gate => self.fetch_gates_batch(names, user).await.map(|results| {
+                    results
+                        .into_iter()
+                        .map(|result| (result.name.clone(), Ok(EvaluationResult::Gate(result))))
+                        .collect()
+                }),
+                EntityType::Config => {
+                    self.fetch_configs_batch(names.clone(), user)
+                        .await
+                        .map(|results| {
+                            names
+                                .into_iter()
+                                .zip(results)
+                                .map(|(name, result)| (name, result.map(EvaluationResult::Config)))
+                                .collect()
+                        })
+                }
+                EntityType::Layer => {
+                    self.fetch_layers_batch(names.clone(), user)
+                        .await
+                        .map(|results| {
+                            names
+                                .into_iter()
+                                .zip(results)
+                                .map(|(name, result)| (name, result.map(EvaluationResult::Layer)))
+                                .collect()
+                        })
+                }
+            };
+
+            let mut fetched = match fetched {
+                Ok(fetched) => fetched,
+                Err(e) => {
+                    for (_, _, sender) in leading {
+                        sender.send_replace(Some(Err(e.clone())));
+                    }
+                    return Err(e);
+                }
+            };
+
+            let mut first_error = None;
+            for (name, cache_key, sender) in leading {
+                let outcome = match fetched.remove(&name) {
+                    Some(Ok(result)) => {
+                        if !self.invalidated_since(generation) {
+                            self.cache_evaluation(cache_key, result.clone()).await;
+                        }
+                        results.push(result.clone());
+                        Ok(Some(result))
+                    }
+                    Some(Err(e)) => {
+                        first_error.get_or_insert_with(|| e.clone());
+                        Err(e)
+                    }
+                    None => Ok(None),
+                };
+                sender.send_replace(Some(outcome));
+            }
+            if let Some(e) = first_error {
+                return Err(e);
+            }
+        }
+
+        let mut orphaned = Vec::new();
+        for (name, mut receiver) in following {
+            let outcome = match receiver.wait_for(Option::is_some).await {
+                Ok(outcome) => outcome.clone(),
+                Err(_) => None,
+            };
+            match outcome {
+                Some(Ok(result)) => results.extend(result),
+                Some(Err(e)) => return Err(e),
+                // The fetching caller was dropped before sending a result
+                None => orphaned.push(name),
+            }
+        }
+        if !orphaned.is_empty() {
+            results.extend(Box::pin(self.fetch_coalesced(entity_type, orphaned, user)).await?);
+        }
+
+        Ok(results)
+    }
+
+    /// Looks up a cache miss in the shared cache store
+    ///
+    /// Store errors and lookups slower than `cache_store_timeout` are logged
+    /// and treated as a miss.
+    async fn read_through(
+        &self,
+        cache_key: &CacheKey,
+        generation: u64,
+    ) -> Option<EvaluationResult> {
+        let cache_store = self.config.cache_store.as_ref()?;
+        let ttl = self
+            .config
+            .cache_ttl_for(&cache_key.entity_type, &cache_key.entity_name)?;
+        let key = cache_store::entry_key(
+            &cache_key.entity_type,
+            &cache_key.entity_name,
+            &cache_key.user_hash,
+        );
+        let lookup = tokio::time::timeout(self.config.cache_store_timeout, cache_store.get(&key));
+        let entry = match lookup.await {
+            Ok(Ok(entry)) => entry?,
+            Ok(Err(e)) => {
+                warn!("Cache store lookup failed for {}: {}", key, e);
+                return None;
+            }
+            Err(_) => {
+                warn!(
+                    "Cache store lookup for {} timed out after {:?}",
+                    key, self.config.cache_store_timeout
+                );
+                return None;
+            }
+        };
+
+        let (stored_key, cached, age) = restore_entry(entry);
+        if stored_key != *cache_key || age >= ttl || self.invalidated_since(generation) {
+            return None;
+        }
+        if let Some(last_known) = &self.last_known {
+            last_known.insert(stored_key.clone(), cached.clone()).await;
+        }
+        self.cache_metrics.record_insert();
+        self.cache.insert(stored_key, cached.clone()).await;
+
+        let mut result = cached.result;
+        result.set_reason(result.reason().served_from_cache());
+        Some(result)
+    }
+
     async fn fetch_gates_batch(
         &self,
         gate_names: Vec<String>,
@@ -450,7 +1918,7 @@ impl StatsigClient {
         &self,
         config_names: Vec<String>,
         user: &User,
-    ) -> Result<Vec<ConfigEvaluationResult>> {
+    ) -> Result<Vec<Result<ConfigEvaluationResult>>> {
         let (response_tx, response_rx) = oneshot::channel();
 
         let request = BatchRequest::GetConfigs {
@@ -466,28 +1934,369 @@ impl StatsigClient {
 
         response_rx
             .await
-            .map_err(|_| StatsigError::batch_processor("Batch processor response channel closed"))?
+            .map_err(|_| StatsigError::batch_processor("Batch processor response channel closed"))
     }
 
-    /// Get cache performance metrics
-    ///
-    /// Returns a snapshot of cache performance metrics including hit ratio,
-    /// total requests, and other useful statistics for monitoring.
-    ///
-    /// # Returns
-    /// A summary of cache metrics
-    pub fn cache_metrics(&self) -> CacheMetricsSummary {
-        self.cache_metrics.summary()
+    async fn fetch_layers_batch(
+        &self,
+        layer_names: Vec<String>,
+        user: &User,
+    ) -> Result<Vec<Result<LayerEvaluationResult>>> {
+        let (response_tx, response_rx) = oneshot::channel();
+
+        let request = BatchRequest::GetLayers {
+            layer_names,
+            user: user.clone(),
+            response_tx,
+        };
+
+        self.batch_sender
+            .send(request)
+            .await
+            .map_err(|_| StatsigError::batch_processor("Batch processor channel closed"))?;
+
+        response_rx
+            .await
+            .map_err(|_| StatsigError::batch_processor("Batch processor response channel closed"))
     }
 
-    /// Reset cache metrics
+    /// Shut down the client, draining pending work
     ///
-    /// Resets all cache performance counters to zero. Useful for
-    /// periodic monitoring or testing scenarios.
-    pub fn reset_cache_metrics(&self) {
-        self.cache_metrics.reset();
-    }
-}
+    /// Stops accepting new evaluations and events, lets the batch processor
+    /// finish queued gate and config requests, waits for in-flight
+    /// evaluations to log their exposures, then flushes queued events. Waits
+    /// for background tasks to exit or `shutdown_timeout` to elapse, then
+    /// flushes the cache store. Calling this more than once is a no-op.
+    pub async fn shutdown(&self) {
+        if self.is_shut_down.swap(true, Ordering::SeqCst) {
+            return;
+        }
+
+        info!("Shutting down Statsig client");
+
+        let batch_task = match self.batch_task.lock() {
+            Ok(mut task) => task.take(),
+            Err(_) => None,
+        };
+        let handles = match self.background_tasks.lock() {
+            Ok(mut tasks) => std::mem::take(&mut *tasks),
+            Err(_) => Vec::new(),
+        };
+
+        let drain = async {
+            // The event logger must outlive the evaluations the batch
+            // processor is still answering, or their exposures are dropped
+            let _ = self.batch_shutdown_tx.send(());
+            if let Some(handle) = batch_task {
+                if let Err(e) = handle.await {
+                    warn!("Batch processor failed during shutdown: {}", e);
+                }
+            }
+            self.active_evaluations.wait_idle().await;
+
+            let _ = self.shutdown_tx.send(());
+            for handle in handles {
+                if let Err(e) = handle.await {
+                    warn!("Background task failed during shutdown: {}", e);
+                }
+            }
+        };
+
+        if tokio::time::timeout(self.config.shutdown_timeout, drain)
+            .await
+            .is_err()
+        {
+            warn!(
+                "Timed out after {:?} waiting for background tasks to shut down",
+                self.config.shutdown_timeout
+            );
+            // Still stop the remaining tasks if the batch drain never finished
+            let _ = self.shutdown_tx.send(());
+        }
+
+        if let Some(cache_store) = &self.config.cache_store {
+            if let Err(e) = cache_store.flush().await {
+                warn!("Failed to flush cache store during shutdown: {}", e);
+            }
+        }
+    }
+
+    /// Whether an invalidation happened after `generation` was read
+    fn invalidated_since(&self, generation: u64) -> bool {
+        self.invalidations.load(Ordering::SeqCst) != generation
+    }
+
+    fn ensure_running(&self) -> Result<()> {
+        if self.is_shut_down.load(Ordering::SeqCst) {
+            return Err(StatsigError::ShutDown);
+        }
+        Ok(())
+    }
+
+    /// Evicts every cached evaluation of `gate_name`, for all users
+    ///
+    /// The next check fetches a fresh value. Last-known values kept for
+    /// `offline_fallback` are not affected.
+    pub async fn invalidate_gate(&self, gate_name: &str) {
+        self.invalidate(EntrySelector {
+            entity_type: Some(EntityType::Gate),
+            name: Some(gate_name.to_string()),
+            user_hash: None,
+        })
+        .await;
+    }
+
+    /// Evicts every cached evaluation of `config_name`, for all users
+    ///
+    /// Experiments are cached as configs and are invalidated the same way.
+    pub async fn invalidate_config(&self, config_name: &str) {
+        self.invalidate(EntrySelector {
+            entity_type: Some(EntityType::Config),
+            name: Some(config_name.to_string()),
+            user_hash: None,
+        })
+        .await;
+    }
+
+    /// Evicts every cached evaluation of `layer_name`, for all users
+    pub async fn invalidate_layer(&self, layer_name: &str) {
+        self.invalidate(EntrySelector {
+            entity_type: Some(EntityType::Layer),
+            name: Some(layer_name.to_string()),
+            user_hash: None,
+        })
+        .await;
+    }
+
+    /// Evicts every cached evaluation for `user`
+    pub async fn invalidate_user(&self, user: &User) {
+        self.invalidate(EntrySelector {
+            user_hash: Some(user.hash_for_cache()),
+            ..Default::default()
+        })
+        .await;
+    }
+
+    /// Evicts every cached evaluation
+    pub async fn clear_cache(&self) {
+        self.invalidate(EntrySelector::default()).await;
+    }
+
+    /// Evicts matching entries from the cache and the cache store
+    async fn invalidate(&self, selector: EntrySelector) {
+        self.invalidations.fetch_add(1, Ordering::SeqCst);
+        // New callers must not join fetches that started before the invalidation
+        self.in_flight
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .retain(|key, _| {
+                !selector.matches_parts(&key.entity_type, &key.entity_name, &key.user_hash)
+            });
+
+        if selector == EntrySelector::default() {
+            self.cache.invalidate_all();
+        } else {
+            let predicate = selector.clone();
+            let registered = self.cache.invalidate_entries_if(move |key, _| {
+                predicate.matches_parts(&key.entity_type, &key.entity_name, &key.user_hash)
+            });
+            if let Err(e) = registered {
+                warn!(
+                    "Failed to invalidate cache entries, clearing the cache: {}",
+                    e
+                );
+                self.cache.invalidate_all();
+            }
+        }
+
+        if let Some(cache_store) = &self.config.cache_store {
+            if let Err(e) = cache_store.invalidate(&selector).await {
+                warn!(
+                    "Failed to invalidate cache store entries {}: {}",
+                    selector.key_pattern(),
+                    e
+                );
+            }
+        }
+    }
+
+    /// Snapshot of the cached evaluations, for debugging
+    ///
+    /// Entries that have expired but not yet been evicted may be included.
+    pub fn cache_entries(&self) -> impl Iterator<Item = CacheEntry> + '_ {
+        self.cache.iter().map(|(key, cached)| {
+            let value = match &cached.result {
+                EvaluationResult::Gate(result) => Value::Bool(result.value),
+                EvaluationResult::Config(result) => result.value.clone(),
+                EvaluationResult::Layer(result) => result.value.clone(),
+            };
+            CacheEntry {
+                entity_type: key.entity_type.clone(),
+                name: key.entity_name.clone(),
+                user_hash: key.user_hash.clone(),
+                age: cached.timestamp.elapsed(),
+                value,
+            }
+        })
+    }
+
+    /// State of the circuit breaker around the Statsig API
+    ///
+    /// `None` if `circuit_breaker` is not configured or the client was built
+    /// with a transport that has no breaker.
+    pub fn circuit_state(&self) -> Option<CircuitState> {
+        self.transport.circuit_state()
+    }
+
+    /// Get cache performance metrics
+    ///
+    /// Returns a snapshot of cache performance metrics including hit ratio,
+    /// total requests, and other useful statistics for monitoring.
+    /// Evictions are counted as the cache processes them, so they and the
+    /// entry count gauges may briefly lag behind recent operations.
+    ///
+    /// # Returns
+    /// A summary of cache metrics
+    pub fn cache_metrics(&self) -> CacheMetricsSummary {
+        CacheMetricsSummary {
+            entry_count: self.cache.entry_count(),
+            weighted_size: self.cache.weighted_size(),
+            ..self.cache_metrics.summary()
+        }
+    }
+
+    /// Reset cache metrics
+    ///
+    /// Resets all cache performance counters to zero. Useful for
+    /// periodic monitoring or testing scenarios.
+    pub fn reset_cache_metrics(&self) {
+        self.cache_metrics.reset();
+    }
+}
+
+impl Drop for StatsigClient {
+    /// Best-effort shutdown: signals background tasks to drain and flush
+    /// without waiting for them to finish.
+    fn drop(&mut self) {
+        if !self.is_shut_down.swap(true, Ordering::SeqCst) {
+            let _ = self.batch_shutdown_tx.send(());
+            let _ = self.shutdown_tx.send(());
+        }
+    }
+}
+
+/// Inserts an evaluation into the cache and the last-known store, writing
+/// through to the persistent cache store in the background if one is configured
+async fn store_evaluation(
+    cache: Option<&Cache<CacheKey, CachedEvaluation>>,
+    last_known: Option<&Cache<CacheKey, CachedEvaluation>>,
+    cache_store: Option<&Arc<dyn CacheStore>>,
+    cache_metrics: &CacheMetrics,
+    cache_key: CacheKey,
+    result: EvaluationResult,
+) {
+    if let Some(cache_store) = cache_store {
+        let entry = StoredEvaluation {
+            user_hash: cache_key.user_hash.clone(),
+            stored_at_ms: now_ms(),
+            result: result.clone().into(),
+            reason: result.reason(),
+        };
+        let cache_store = cache_store.clone();
+        let entity_name = cache_key.entity_name.clone();
+        tokio::spawn(async move {
+            if let Err(e) = cache_store.store(entry).await {
+                warn!("Failed to persist cache entry {}: {}", entity_name, e);
+            }
+        });
+    }
+
+    let cached = CachedEvaluation {
+        result,
+        timestamp: std::time::Instant::now(),
+        typed: Default::default(),
+    };
+    if let Some(last_known) = last_known {
+        last_known.insert(cache_key.clone(), cached.clone()).await;
+    }
+    if let Some(cache) = cache {
+        cache_metrics.record_insert();
+        cache.insert(cache_key, cached).await;
+    }
+}
+
+/// Converts a stored entry back into a cache entry, returning it with its age
+///
+/// The entry keeps its original fetch time where the platform can represent
+/// it; entries older than that are pinned to the earliest reachable instant,
+/// so callers should judge freshness by the returned age.
+fn restore_entry(entry: StoredEvaluation) -> (CacheKey, CachedEvaluation, std::time::Duration) {
+    let age =
+        std::time::Duration::from_millis(now_ms().saturating_sub(entry.stored_at_ms).max(0) as u64);
+    let timestamp = instant_before_now(age);
+    let mut result = EvaluationResult::from(entry.result);
+    result.set_reason(entry.reason);
+    let cache_key = CacheKey {
+        entity_type: result.entity_type(),
+        entity_name: result.name().to_string(),
+        user_hash: entry.user_hash,
+    };
+    let cached = CachedEvaluation {
+        result,
+        timestamp,
+        typed: Default::default(),
+    };
+    (cache_key, cached, age)
+}
+
+/// The instant `age` ago, or the earliest instant `Instant` can represent
+/// if that predates it (e.g. entries older than the host's uptime)
+fn instant_before_now(age: std::time::Duration) -> std::time::Instant {
+    let now = std::time::Instant::now();
+    if let Some(instant) = now.checked_sub(age) {
+        return instant;
+    }
+    let mut reachable = std::time::Duration::ZERO;
+    let mut step = age / 2;
+    while step >= std::time::Duration::from_millis(1) {
+        if now.checked_sub(reachable + step).is_some() {
+            reachable += step;
+        }
+        step /= 2;
+    }
+    now - reachable
+}
+
+/// Loads unexpired entries from the persistent cache store
+async fn warm_load(
+    cache_store: &dyn CacheStore,
+    cache: &Cache<CacheKey, CachedEvaluation>,
+    last_known: Option<&Cache<CacheKey, CachedEvaluation>>,
+    config: &StatsigClientConfig,
+) {
+    let entries = match cache_store.load().await {
+        Ok(entries) => entries,
+        Err(e) => {
+            warn!("Failed to load persisted cache, starting cold: {}", e);
+            return;
+        }
+    };
+
+    let mut loaded = 0;
+    for entry in entries {
+        let (cache_key, cached, age) = restore_entry(entry);
+        // Expired entries are still useful as offline fallbacks
+        if let Some(last_known) = last_known {
+            last_known.insert(cache_key.clone(), cached.clone()).await;
+        }
+        let ttl = config.cache_ttl_for(&cache_key.entity_type, &cache_key.entity_name);
+        if ttl.is_some_and(|ttl| age < ttl) {
+            cache.insert(cache_key, cached).await;
+            loaded += 1;
+        }
+    }
+    info!("Warm-loaded {} cached evaluations", loaded);
+}
 
 fn validate_entity_name(kind: &str, name: &str) -> Result<()> {
     let len = name.chars().count();
@@ -510,6 +2319,7 @@ fn now_ms() -> i64 {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::time::Duration;
 
     #[tokio::test]
     async fn test_client_creation() {
@@ -531,6 +2341,822 @@ mod tests {
         assert_eq!(user.country, Some("US".to_string()));
     }
 
+    #[tokio::test]
+    async fn test_offline_fallback_serves_default() {
+        let config = StatsigClientConfig::builder()
+            .api_key("test_key")
+            .base_url("http://127.0.0.1:1".to_string())
+            .retry_attempts(1)
+            .retry_delay(std::time::Duration::from_millis(1))
+            .offline_fallback(true)
+            .offline_default_gate_value(true)
+            .build();
+        let client = StatsigClient::with_config(config).await.unwrap();
+        let user = User::builder().user_id("test_user").build().unwrap();
+
+        assert!(client.check_gate("offline-gate", &user).await.unwrap());
+
+        let evaluation = client
+            .get_config_evaluation("offline-config", &user)
+            .await
+            .unwrap();
+        assert_eq!(evaluation.reason, EvaluationReason::Default);
+        assert!(evaluation.reason.is_fallback());
+    }
+
+    /// Serves evaluations from the mock after `evaluation_delay`, and never
+    /// finishes a spec download
+    #[derive(Debug)]
+    struct SlowTransport {
+        mock: MockTransport,
+        evaluation_delay: Duration,
+    }
+
+    #[async_trait::async_trait]
+    impl Transport for SlowTransport {
+        async fn check_gates(
+            &self,
+            gate_names: Vec<String>,
+            user: &User,
+        ) -> Result<Vec<GateEvaluationResult>> {
+            tokio::time::sleep(self.evaluation_delay).await;
+            self.mock.check_gates(gate_names, user).await
+        }
+
+        async fn get_config(
+            &self,
+            config_name: &str,
+            user: &User,
+        ) -> Result<ConfigEvaluationResult> {
+            tokio::time::sleep(self.evaluation_delay).await;
+            self.mock.get_config(config_name, user).await
+        }
+
+        async fn get_layer(&self, layer_name: &str, user: &User) -> Result<LayerEvaluationResult> {
+            tokio::time::sleep(self.evaluation_delay).await;
+            self.mock.get_layer(layer_name, user).await
+        }
+
+        async fn download_config_specs(&self, _since_time: u64) -> Result<ConfigSpecs> {
+            std::future::pending().await
+        }
+
+        async fn log_events(
+            &self,
+            user: Option<&User>,
+            events: &[StatsigEvent],
+        ) -> Result<LogEventResponse> {
+            self.mock.log_events(user, events).await
+        }
+    }
+
+    #[tokio::test]
+    async fn test_hung_spec_download_does_not_block_creation() {
+        let mock = MockTransport::new();
+        mock.set_gate("new_gate", true);
+        let config = StatsigClientConfig::builder()
+            .api_key("test_key")
+            .local_evaluation(true)
+            .timeout(Duration::from_secs(1))
+            .build();
+        let transport = Arc::new(SlowTransport {
+            mock: mock.clone(),
+            evaluation_delay: Duration::ZERO,
+        });
+        let client = tokio::time::timeout(
+            Duration::from_secs(5),
+            StatsigClient::with_transport(config, transport),
+        )
+        .await
+        .expect("client creation should not wait on the spec download")
+        .unwrap();
+        let user = User::builder().user_id("test_user").build().unwrap();
+
+        // Gates missing from the local specs are evaluated by the server
+        assert!(client.check_gate("new_gate", &user).await.unwrap());
+        assert_eq!(mock.evaluation_request_count(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_bootstrapped_specs_skip_the_blocking_initial_sync() {
+        let mock = MockTransport::new();
+        let config = StatsigClientConfig::builder()
+            .api_key("test_key")
+            .local_evaluation(true)
+            .timeout(Duration::from_secs(30))
+            .build();
+        let transport = Arc::new(SlowTransport {
+            mock: mock.clone(),
+            evaluation_delay: Duration::ZERO,
+        });
+        let specs = r#"{"time": 1, "feature_gates": [{
+            "name": "local_gate", "type": "feature_gate", "salt": "s", "enabled": true,
+            "defaultValue": false,
+            "rules": [{"name": "all", "id": "r", "passPercentage": 100, "returnValue": true,
+                "idType": "userID", "conditions": [{"type": "public", "idType": "userID"}]}]
+        }]}"#;
+
+        let client = tokio::time::timeout(
+            Duration::from_secs(1),
+            StatsigClient::build(config, transport, Some(Bootstrap::parse(specs).unwrap())),
+        )
+        .await
+        .expect("bootstrapped client creation should not wait on the spec download")
+        .unwrap();
+        let user = User::builder().user_id("test_user").build().unwrap();
+
+        assert!(client.check_gate("local_gate", &user).await.unwrap());
+        assert_eq!(mock.evaluation_request_count(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_offline_fallback_covers_local_rate_limiting() {
+        let config = StatsigClientConfig::builder()
+            .api_key("test_key")
+            .base_url("http://127.0.0.1:1".to_string())
+            .retry_attempts(1)
+            .retry_delay(Duration::from_millis(1))
+            .offline_fallback(true)
+            .offline_default_gate_value(true)
+            .rate_limit(
+                RateLimitConfig::builder()
+                    .requests_per_second(0.01)
+                    .burst(1)
+                    .max_wait(Duration::ZERO)
+                    .build(),
+            )
+            .build();
+        let client = StatsigClient::with_config(config).await.unwrap();
+        let user = User::builder().user_id("test_user").build().unwrap();
+
+        // The first check uses the whole budget; the second is rejected locally
+        assert!(client.check_gate("first-gate", &user).await.unwrap());
+        let evaluation = client
+            .get_config_evaluation("rate-limited-config", &user)
+            .await
+            .unwrap();
+        assert_eq!(evaluation.reason, EvaluationReason::Default);
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_rejects_new_work() {
+        let client = StatsigClient::new("test_key").await.unwrap();
+        let user = User::builder().user_id("test_user").build().unwrap();
+
+        client.shutdown().await;
+        client.shutdown().await;
+
+        assert!(matches!(
+            client.check_gate("demo-gate", &user).await,
+            Err(StatsigError::ShutDown)
+        ));
+        assert!(matches!(
+            client.log_event("page_view", &user),
+            Err(StatsigError::ShutDown)
+        ));
+        let event = StatsigEvent::builder()
+            .event_name("purchase".to_string())
+            .build();
+        assert!(matches!(
+            client.log_events_immediately(vec![event], &user).await,
+            Err(StatsigError::ShutDown)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_logs_exposures_of_queued_evaluations() {
+        let mock = MockTransport::new();
+        let gate_names = ["gate_a", "gate_b", "gate_c", "gate_d"];
+        for gate_name in gate_names {
+            mock.set_gate(gate_name, true);
+        }
+        // Requests stay queued until the shutdown drain answers them
+        let config = StatsigClientConfig::builder()
+            .api_key("test_key")
+            .batch_flush_interval(Duration::from_secs(60))
+            .build();
+        let client = Arc::new(
+            StatsigClient::with_transport(config, Arc::new(mock.clone()))
+                .await
+                .unwrap(),
+        );
+        let user = User::builder().user_id("test_user").build().unwrap();
+
+        let checks: Vec<_> = gate_names
+            .into_iter()
+            .map(|gate_name| {
+                let client = client.clone();
+                let user = user.clone();
+                tokio::spawn(async move { client.check_gate(gate_name, &user).await })
+            })
+            .collect();
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        client.shutdown().await;
+        for check in checks {
+            assert!(check.await.unwrap().unwrap());
+        }
+
+        let mut exposed: Vec<_> = mock
+            .logged_events()
+            .into_iter()
+            .filter_map(|event| event.metadata?.get("gate").cloned())
+            .collect();
+        exposed.sort();
+        assert_eq!(exposed, gate_names);
+    }
+
+    #[tokio::test]
+    async fn test_mock_transport_serves_gates_and_records_exposures() {
+        let mock = MockTransport::new();
+        mock.set_gate("new_checkout", true);
+        let config = StatsigClientConfig::new("test_key").unwrap();
+        let client = StatsigClient::with_transport(config, Arc::new(mock.clone()))
+            .await
+            .unwrap();
+        let user = User::builder().user_id("test_user").build().unwrap();
+
+        assert!(client.check_gate("new_checkout", &user).await.unwrap());
+        assert!(!client.check_gate("unknown_gate", &user).await.unwrap());
+        client.log_event("purchase", &user).unwrap();
+        client.shutdown().await;
+
+        let event_names: Vec<_> = mock
+            .logged_events()
+            .into_iter()
+            .map(|event| event.event_name)
+            .collect();
+        assert_eq!(
+            event_names,
+            [
+                "statsig::gate_exposure",
+                "statsig::gate_exposure",
+                "purchase"
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_overrides_short_circuit_evaluation() {
+        let mock = MockTransport::new();
+        mock.set_gate("kill_switch", true);
+        let config = StatsigClientConfig::new("test_key").unwrap();
+        let client = StatsigClient::with_transport(config, Arc::new(mock.clone()))
+            .await
+            .unwrap();
+        let user = User::builder().user_id("test_user").build().unwrap();
+
+        client.override_gate("kill_switch", false, None);
+        client.override_config("settings", serde_json::json!({"a": 1}), Some("test_user"));
+
+        assert!(!client.check_gate("kill_switch", &user).await.unwrap());
+        let evaluation = client
+            .get_config_evaluation("settings", &user)
+            .await
+            .unwrap();
+        assert_eq!(evaluation.reason, EvaluationReason::Override);
+        assert_eq!(mock.evaluation_request_count(), 0);
+
+        assert!(client.remove_gate_override("kill_switch", &OverrideScope::Global));
+        assert!(client.check_gate("kill_switch", &user).await.unwrap());
+        assert_eq!(client.list_overrides().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_bootstrap_snapshot_is_served_without_network() {
+        let config = StatsigClientConfig::builder().local_mode(true).build();
+        let bootstrap = r#"{"evaluations": [{
+            "user": {"userID": "test_user"},
+            "gates": [{"name": "new_checkout", "value": true, "rule_id": "r", "group_name": null}]
+        }]}"#;
+        let client = StatsigClient::with_bootstrap(config, bootstrap)
+            .await
+            .unwrap();
+        let user = User::builder().user_id("test_user").build().unwrap();
+
+        let results = client
+            .evaluate_gates(vec!["new_checkout".to_string()], &user)
+            .await;
+        let gate_result = &results.unwrap()["new_checkout"];
+        assert!(gate_result.value);
+        assert_eq!(gate_result.reason, EvaluationReason::Bootstrap);
+    }
+
+    #[tokio::test]
+    async fn test_persisted_cache_is_warm_loaded() {
+        let dir = std::env::temp_dir().join(format!("statsig-cache-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("cache.jsonl");
+        let user = User::builder().user_id("test_user").build().unwrap();
+
+        let mock = MockTransport::new();
+        mock.set_gate("new_checkout", true);
+        let config = StatsigClientConfig::builder()
+            .api_key("test_key")
+            .cache_store(Arc::new(FileCacheStore::new(&path, 100)))
+            .build();
+        let client = StatsigClient::with_transport(config.clone(), Arc::new(mock.clone()))
+            .await
+            .unwrap();
+        assert!(client.check_gate("new_checkout", &user).await.unwrap());
+        client.shutdown().await;
+
+        // A restarted client serves the persisted value without a request
+        let mock = MockTransport::new();
+        let client = StatsigClient::with_transport(config, Arc::new(mock.clone()))
+            .await
+            .unwrap();
+        let results = client
+            .evaluate_gates(vec!["new_checkout".to_string()], &user)
+            .await
+            .unwrap();
+        assert!(results["new_checkout"].value);
+        assert_eq!(results["new_checkout"].reason, EvaluationReason::Cache);
+        assert_eq!(mock.evaluation_request_count(), 0);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_persisted_entries_keep_reason_and_survive_any_age() {
+        let dir = std::env::temp_dir().join(format!("statsig-cache-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let store = FileCacheStore::new(dir.join("cache.jsonl"), 100);
+        let user = User::builder().user_id("test_user").build().unwrap();
+        let entry = |name: &str, stored_at_ms: i64, reason: EvaluationReason| StoredEvaluation {
+            user_hash: user.hash_for_cache(),
+            stored_at_ms,
+            result: StoredResult::Gate(GateEvaluationResult {
+                name: name.to_string(),
+                value: true,
+                rule_id: None,
+                group_name: None,
+                secondary_exposures: Vec::new(),
+                reason,
+            }),
+            reason,
+        };
+        store
+            .store(entry(
+                "bootstrap_gate",
+                now_ms(),
+                EvaluationReason::Bootstrap,
+            ))
+            .await
+            .unwrap();
+        // Older than any host's uptime
+        let twenty_years_ms = 20 * 365 * 24 * 60 * 60 * 1000;
+        store
+            .store(entry(
+                "old_gate",
+                now_ms() - twenty_years_ms,
+                EvaluationReason::Network,
+            ))
+            .await
+            .unwrap();
+        store.flush().await.unwrap();
+
+        let mock = MockTransport::new();
+        mock.set_error(Some(StatsigError::network("unreachable")));
+        let config = StatsigClientConfig::builder()
+            .api_key("test_key")
+            .cache_store(Arc::new(store))
+            .offline_fallback(true)
+            .build();
+        let client = StatsigClient::with_transport(config, Arc::new(mock))
+            .await
+            .unwrap();
+
+        let details = client
+            .check_gate_with_details("bootstrap_gate", &user)
+            .await;
+        assert!(details.value);
+        assert_eq!(details.reason, EvaluationReason::Bootstrap);
+
+        let details = client.check_gate_with_details("old_gate", &user).await;
+        assert!(details.value);
+        assert_eq!(details.reason, EvaluationReason::Fallback);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_shared_cache_store_is_read_through() {
+        let shared = MemoryCacheStore::new();
+        let user = User::builder().user_id("test_user").build().unwrap();
+        let config = StatsigClientConfig::builder()
+            .api_key("test_key")
+            .cache_store(Arc::new(shared.clone()))
+            .build();
+
+        let first = MockTransport::new();
+        first.set_gate("new_checkout", true);
+        let client = StatsigClient::with_transport(config.clone(), Arc::new(first))
+            .await
+            .unwrap();
+        assert!(client.check_gate("new_checkout", &user).await.unwrap());
+        // Written through in the background
+        tokio::task::yield_now().await;
+        assert_eq!(shared.len(), 1);
+
+        // Another replica reuses the shared evaluation instead of fetching
+        let second = MockTransport::new();
+        let replica = StatsigClient::with_transport(config, Arc::new(second.clone()))
+            .await
+            .unwrap();
+        assert!(replica.check_gate("new_checkout", &user).await.unwrap());
+        assert_eq!(second.evaluation_request_count(), 0);
+        assert_eq!(replica.cache_metrics().inserts, 1);
+    }
+
+    /// Shared store whose lookups never complete
+    #[derive(Debug)]
+    struct HungCacheStore;
+
+    #[async_trait::async_trait]
+    impl CacheStore for HungCacheStore {
+        async fn get(&self, _key: &str) -> Result<Option<StoredEvaluation>> {
+            std::future::pending().await
+        }
+
+        async fn store(&self, _entry: StoredEvaluation) -> Result<()> {
+            Ok(())
+        }
+
+        async fn invalidate(&self, _selector: &EntrySelector) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_hung_cache_store_falls_through_to_fetch() {
+        let mock = MockTransport::new();
+        mock.set_gate("new_checkout", true);
+        let config = StatsigClientConfig::builder()
+            .api_key("test_key")
+            .cache_store(Arc::new(HungCacheStore))
+            .cache_store_timeout(Duration::from_millis(20))
+            .build();
+        let client = StatsigClient::with_transport(config, Arc::new(mock.clone()))
+            .await
+            .unwrap();
+        let user = User::builder().user_id("test_user").build().unwrap();
+
+        let result = tokio::time::timeout(
+            Duration::from_secs(5),
+            client.check_gate("new_checkout", &user),
+        )
+        .await
+        .expect("a hung cache store must not stall evaluations");
+        assert!(result.unwrap());
+        assert_eq!(mock.evaluation_request_count(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_invalidation_forces_refetch() {
+        let mock = MockTransport::new();
+        mock.set_gate("kill_switch", false);
+        mock.set_config("ui_settings", serde_json::json!({"theme": "dark"}));
+        let config = StatsigClientConfig::builder().api_key("test_key").build();
+        let client = StatsigClient::with_transport(config, Arc::new(mock.clone()))
+            .await
+            .unwrap();
+        let user = User::builder().user_id("test_user").build().unwrap();
+
+        assert!(!client.check_gate("kill_switch", &user).await.unwrap());
+        client.get_config("ui_settings", &user).await.unwrap();
+        let mut entries: Vec<_> = client.cache_entries().map(|entry| entry.name).collect();
+        entries.sort();
+        assert_eq!(entries, ["kill_switch", "ui_settings"]);
+
+        mock.set_gate("kill_switch", true);
+        assert!(!client.check_gate("kill_switch", &user).await.unwrap());
+        client.invalidate_gate("kill_switch").await;
+        assert!(client.check_gate("kill_switch", &user).await.unwrap());
+
+        client.clear_cache().await;
+        assert_eq!(client.cache_entries().count(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_layer_invalidation_forces_refetch() {
+        let mock = MockTransport::new();
+        let layer = |color: &str| LayerEvaluationResult {
+            name: "checkout_layer".to_string(),
+            value: serde_json::json!({ "button_color": color }),
+            rule_id: None,
+            group_name: None,
+            allocated_experiment_name: None,
+            explicit_parameters: Vec::new(),
+            secondary_exposures: Vec::new(),
+            undelegated_secondary_exposures: Vec::new(),
+            reason: EvaluationReason::Network,
+        };
+        mock.set_layer_result(layer("blue"));
+        let config = StatsigClientConfig::builder().api_key("test_key").build();
+        let client = StatsigClient::with_transport(config, Arc::new(mock.clone()))
+            .await
+            .unwrap();
+        let user = User::builder().user_id("test_user").build().unwrap();
+
+        let color = |layer: Layer| layer.get("button_color", String::new());
+        assert_eq!(
+            color(client.get_layer("checkout_layer", &user).await.unwrap()),
+            "blue"
+        );
+
+        mock.set_layer_result(layer("green"));
+        assert_eq!(
+            color(client.get_layer("checkout_layer", &user).await.unwrap()),
+            "blue"
+        );
+        client.invalidate_layer("checkout_layer").await;
+        assert_eq!(
+            color(client.get_layer("checkout_layer", &user).await.unwrap()),
+            "green"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_evictions_are_counted_by_cause() {
+        let mock = MockTransport::new();
+        let config = StatsigClientConfig::builder()
+            .api_key("test_key")
+            .cache_max_capacity(1)
+            .build();
+        let client = StatsigClient::with_transport(config, Arc::new(mock))
+            .await
+            .unwrap();
+        let user = User::builder().user_id("test_user").build().unwrap();
+
+        client.check_gate("gate_a", &user).await.unwrap();
+        client.check_gate("gate_b", &user).await.unwrap();
+        client.get_config("config_a", &user).await.unwrap();
+        client.cache.run_pending_tasks().await;
+        let metrics = client.cache_metrics();
+        assert_eq!(metrics.size_evictions, 2);
+        assert_eq!(metrics.entry_count, 1);
+        assert_eq!(metrics.gates.misses, 2);
+        assert_eq!(metrics.configs.misses, 1);
+
+        client.clear_cache().await;
+        client.cache.run_pending_tasks().await;
+        let metrics = client.cache_metrics();
+        assert_eq!(metrics.explicit_evictions, 1);
+        assert_eq!(metrics.evictions, 3);
+        assert_eq!(metrics.entry_count, 0);
+    }
+
+    #[tokio::test]
+    async fn test_never_cache_entities_are_always_fetched() {
+        let mock = MockTransport::new();
+        let config = StatsigClientConfig::builder()
+            .api_key("test_key")
+            .cache_ttl_policy(CacheTtlPolicy::new().never_cache("kill_switch"))
+            .build();
+        let client = StatsigClient::with_transport(config, Arc::new(mock.clone()))
+            .await
+            .unwrap();
+        let user = User::builder().user_id("test_user").build().unwrap();
+
+        client.check_gate("kill_switch", &user).await.unwrap();
+        client.check_gate("kill_switch", &user).await.unwrap();
+        client.check_gate("new_checkout", &user).await.unwrap();
+        client.check_gate("new_checkout", &user).await.unwrap();
+        assert_eq!(mock.evaluation_request_count(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_evaluation_details_report_source() {
+        let mock = MockTransport::new();
+        mock.set_gate("new_checkout", true);
+        let config = StatsigClientConfig::builder().api_key("test_key").build();
+        let client = StatsigClient::with_transport(config, Arc::new(mock.clone()))
+            .await
+            .unwrap();
+        let user = User::builder().user_id("test_user").build().unwrap();
+
+        let details = client.check_gate_with_details("new_checkout", &user).await;
+        assert!(details.value);
+        assert_eq!(details.reason, EvaluationReason::Network);
+        assert_eq!(details.cache_age, None);
+
+        let details = client.check_gate_with_details("new_checkout", &user).await;
+        assert_eq!(details.reason, EvaluationReason::Cache);
+        assert!(details.cache_age.is_some());
+
+        mock.set_error(Some(StatsigError::api(400, "bad request")));
+        let details = client.get_config_with_details("ui_settings", &user).await;
+        assert_eq!(details.reason, EvaluationReason::Error);
+        assert_eq!(details.value, Value::Null);
+        assert!(matches!(
+            details.error,
+            Some(StatsigError::Api { status: 400, .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_or_variants_serve_caller_default_on_error() {
+        let mock = MockTransport::new();
+        mock.set_error(Some(StatsigError::api(400, "bad request")));
+        let config = StatsigClientConfig::builder().api_key("test_key").build();
+        let client = StatsigClient::with_transport(config, Arc::new(mock))
+            .await
+            .unwrap();
+        let user = User::builder().user_id("test_user").build().unwrap();
+
+        assert!(client.check_gate_or("new_checkout", &user, true).await);
+        let default = serde_json::json!({"theme": "light"});
+        assert_eq!(
+            client
+                .get_config_or("ui_settings", &user, default.clone())
+                .await,
+            default
+        );
+        assert!(!client.check_gate_or("x", &user, false).await);
+        assert_eq!(client.evaluation_error_count(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_or_variants_replace_offline_defaults_but_keep_last_known() {
+        let mock = MockTransport::new();
+        mock.set_gate("new_checkout", true);
+        let config = StatsigClientConfig::builder()
+            .api_key("test_key")
+            .offline_fallback(true)
+            .build();
+        let client = StatsigClient::with_transport(config, Arc::new(mock.clone()))
+            .await
+            .unwrap();
+        let user = User::builder().user_id("test_user").build().unwrap();
+
+        assert!(client.check_gate("new_checkout", &user).await.unwrap());
+        client.invalidate_gate("new_checkout").await;
+        mock.set_error(Some(StatsigError::network("unreachable")));
+
+        assert!(client.check_gate_or("kill_switch", &user, true).await);
+        assert!(client.check_gate_or("new_checkout", &user, false).await);
+        let default = serde_json::json!({"theme": "light"});
+        assert_eq!(
+            client
+                .get_config_or("ui_settings", &user, default.clone())
+                .await,
+            default
+        );
+    }
+
+    #[tokio::test]
+    async fn test_stale_entries_are_served_while_refreshing() {
+        let mock = MockTransport::new();
+        mock.set_gate("new_checkout", true);
+        let config = StatsigClientConfig::builder()
+            .api_key("test_key")
+            .cache_soft_ttl(Duration::from_millis(50))
+            .batch_flush_interval(Duration::from_millis(10))
+            .build();
+        let client = StatsigClient::with_transport(config, Arc::new(mock.clone()))
+            .await
+            .unwrap();
+        let user = User::builder().user_id("test_user").build().unwrap();
+
+        assert!(client.check_gate("new_checkout", &user).await.unwrap());
+        mock.set_gate("new_checkout", false);
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        // Stale value is served immediately and refreshed in the background
+        assert!(client.check_gate("new_checkout", &user).await.unwrap());
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert!(!client.check_gate("new_checkout", &user).await.unwrap());
+        assert_eq!(mock.evaluation_request_count(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_refresh_started_before_invalidation_is_not_cached() {
+        let mock = MockTransport::new();
+        mock.set_gate("new_checkout", true);
+        let config = StatsigClientConfig::builder()
+            .api_key("test_key")
+            .cache_soft_ttl(Duration::from_millis(50))
+            .batch_flush_interval(Duration::from_millis(10))
+            .build();
+        let transport = Arc::new(SlowTransport {
+            mock: mock.clone(),
+            evaluation_delay: Duration::from_millis(200),
+        });
+        let client = StatsigClient::with_transport(config, transport)
+            .await
+            .unwrap();
+        let user = User::builder().user_id("test_user").build().unwrap();
+
+        assert!(client.check_gate("new_checkout", &user).await.unwrap());
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        // Serves the stale value and starts a refresh that is still blocked
+        assert!(client.check_gate("new_checkout", &user).await.unwrap());
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        client.invalidate_gate("new_checkout").await;
+        tokio::time::sleep(Duration::from_millis(300)).await;
+        assert_eq!(mock.evaluation_request_count(), 2);
+
+        // The refresh landed after the invalidation, so this is a fresh fetch
+        mock.set_gate("new_checkout", false);
+        assert!(!client.check_gate("new_checkout", &user).await.unwrap());
+        assert_eq!(mock.evaluation_request_count(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_short_policy_ttls_are_served_fresh_under_a_longer_soft_ttl() {
+        let mock = MockTransport::new();
+        mock.set_gate("kill_switch_checkout", false);
+        let config = StatsigClientConfig::builder()
+            .api_key("test_key")
+            .cache_ttl(Duration::from_secs(300))
+            .cache_soft_ttl(Duration::from_secs(60))
+            .cache_ttl_policy(
+                CacheTtlPolicy::new().prefix_ttl("kill_switch_", Duration::from_millis(50)),
+            )
+            .build();
+        let client = StatsigClient::with_transport(config, Arc::new(mock.clone()))
+            .await
+            .unwrap();
+        let user = User::builder().user_id("test_user").build().unwrap();
+
+        assert!(
+            !client
+                .check_gate("kill_switch_checkout", &user)
+                .await
+                .unwrap()
+        );
+        mock.set_gate("kill_switch_checkout", true);
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        // Expired rather than stale, so the new value is fetched before returning
+        assert!(
+            client
+                .check_gate("kill_switch_checkout", &user)
+                .await
+                .unwrap()
+        );
+        assert_eq!(mock.evaluation_request_count(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_misses_share_one_fetch() {
+        let mock = MockTransport::new();
+        mock.set_gate("new_checkout", true);
+        let config = StatsigClientConfig::new("test_key").unwrap();
+        let client = Arc::new(
+            StatsigClient::with_transport(config, Arc::new(mock.clone()))
+                .await
+                .unwrap(),
+        );
+        let user = User::builder().user_id("test_user").build().unwrap();
+
+        let mut tasks = tokio::task::JoinSet::new();
+        for _ in 0..50 {
+            let client = client.clone();
+            let user = user.clone();
+            tasks.spawn(async move { client.check_gate("new_checkout", &user).await });
+        }
+        while let Some(result) = tasks.join_next().await {
+            assert!(result.unwrap().unwrap());
+        }
+
+        assert_eq!(mock.evaluation_request_count(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_cancelled_leader_hands_fetch_to_waiters() {
+        let mock = MockTransport::new();
+        mock.set_gate("new_checkout", true);
+        let config = StatsigClientConfig::new("test_key").unwrap();
+        let transport = Arc::new(SlowTransport {
+            mock: mock.clone(),
+            evaluation_delay: Duration::from_millis(200),
+        });
+        let client = StatsigClient::with_transport(config, transport)
+            .await
+            .unwrap();
+        let user = User::builder().user_id("test_user").build().unwrap();
+
+        let mut leader = Box::pin(client.check_gate("new_checkout", &user));
+        let mut follower = Box::pin(client.check_gate("new_checkout", &user));
+        assert!(
+            tokio::time::timeout(Duration::from_millis(20), &mut leader)
+                .await
+                .is_err()
+        );
+        assert!(
+            tokio::time::timeout(Duration::from_millis(20), &mut follower)
+                .await
+                .is_err()
+        );
+
+        // Cancelling the leader must not fail the follower
+        drop(leader);
+        assert!(follower.await.unwrap());
+    }
+
     #[tokio::test]
     #[ignore = "Network integration test (requires Statsig API access)"]
     async fn test_demo_gate() {
@@ -0,0 +1,237 @@
+//! In-memory transport for tests
+//!
+//! [`MockTransport`] serves scripted gate, config and layer results and records
+//! every event it is asked to deliver, so code built on [`crate::StatsigClient`]
+//! can be tested without network access:
+//!
+//! ```rust,no_run
+//! use statsig_client::{MockTransport, StatsigClient, StatsigClientConfig, User};
+//! use std::sync::Arc;
+//!
+//! # async fn example() -> statsig_client::Result<()> {
+//! let mock = MockTransport::new();
+//! mock.set_gate("new_checkout", true);
+//!
+//! let config = StatsigClientConfig::new("secret-test")?;
+//! let client = StatsigClient::with_transport(config, Arc::new(mock.clone())).await?;
+//! let user = User::with_user_id("user-1").build()?;
+//! assert!(client.check_gate("new_checkout", &user).await?);
+//!
+//! client.shutdown().await;
+//! assert!(!mock.logged_events().is_empty());
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::{
+    api::{ConfigEvaluationResult, EvaluationReason, GateEvaluationResult, LayerEvaluationResult},
+    error::{Result, StatsigError},
+    events::{LogEventResponse, StatsigEvent},
+    specs::ConfigSpecs,
+    transport::Transport,
+    user::User,
+};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+/// Scripted [`Transport`] that records logged events
+///
+/// Clones share state, so keep a clone to script results and inspect events
+/// after handing the transport to a client. Unknown gates evaluate to `false`
+/// and unknown configs and layers to an empty object.
+#[derive(Debug, Clone, Default)]
+pub struct MockTransport {
+    state: Arc<Mutex<MockState>>,
+}
+
+#[derive(Debug, Default)]
+struct MockState {
+    gates: HashMap<String, GateEvaluationResult>,
+    configs: HashMap<String, ConfigEvaluationResult>,
+    layers: HashMap<String, LayerEvaluationResult>,
+    config_specs: ConfigSpecs,
+    error: Option<StatsigError>,
+    logged_events: Vec<StatsigEvent>,
+    evaluation_requests: usize,
+}
+
+impl MockTransport {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Scripts the value returned for a gate
+    pub fn set_gate(&self, gate_name: impl Into<String>, value: bool) {
+        let gate_name = gate_name.into();
+        self.set_gate_result(GateEvaluationResult {
+            name: gate_name,
+            value,
+            rule_id: Some("mock".to_string()),
+            group_name: None,
+            secondary_exposures: Vec::new(),
+            reason: EvaluationReason::Network,
+        });
+    }
+
+    /// Scripts the full evaluation returned for a gate
+    pub fn set_gate_result(&self, result: GateEvaluationResult) {
+        self.state().gates.insert(result.name.clone(), result);
+    }
+
+    /// Scripts the value returned for a dynamic config or experiment
+    pub fn set_config(&self, config_name: impl Into<String>, value: Value) {
+        let config_name = config_name.into();
+        self.set_config_result(ConfigEvaluationResult {
+            name: config_name,
+            value,
+            rule_id: Some("mock".to_string()),
+            group_name: None,
+            group: None,
+            secondary_exposures: Vec::new(),
+            reason: EvaluationReason::Network,
+        });
+    }
+
+    /// Scripts the full evaluation returned for a dynamic config or experiment
+    pub fn set_config_result(&self, result: ConfigEvaluationResult) {
+        self.state().configs.insert(result.name.clone(), result);
+    }
+
+    /// Scripts the full evaluation returned for a layer
+    pub fn set_layer_result(&self, result: LayerEvaluationResult) {
+        self.state().layers.insert(result.name.clone(), result);
+    }
+
+    /// Scripts the specs returned by `download_config_specs`
+    pub fn set_config_specs(&self, specs: ConfigSpecs) {
+        self.state().config_specs = specs;
+    }
+
+    /// Makes every request fail with `error` until cleared with `None`
+    pub fn set_error(&self, error: Option<StatsigError>) {
+        self.state().error = error;
+    }
+
+    /// Events delivered through the transport, in order
+    pub fn logged_events(&self) -> Vec<StatsigEvent> {
+        self.state().logged_events.clone()
+    }
+
+    /// Forgets all recorded events
+    pub fn clear_logged_events(&self) {
+        self.state().logged_events.clear();
+    }
+
+    /// Number of gate, config and layer requests served
+    pub fn evaluation_request_count(&self) -> usize {
+        self.state().evaluation_requests
+    }
+
+    fn state(&self) -> MutexGuard<'_, MockState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Counts an evaluation request, failing if an error is scripted
+    fn begin_evaluation(&self) -> Result<MutexGuard<'_, MockState>> {
+        let mut state = self.state();
+        state.evaluation_requests += 1;
+        match &state.error {
+            Some(error) => Err(error.clone()),
+            None => Ok(state),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl Transport for MockTransport {
+    async fn check_gates(
+        &self,
+        gate_names: Vec<String>,
+        _user: &User,
+    ) -> Result<Vec<GateEvaluationResult>> {
+        let state = self.begin_evaluation()?;
+        Ok(gate_names
+            .into_iter()
+            .map(|name| {
+                state
+                    .gates
+                    .get(&name)
+                    .cloned()
+                    .unwrap_or(GateEvaluationResult {
+                        name,
+                        value: false,
+                        rule_id: None,
+                        group_name: None,
+                        secondary_exposures: Vec::new(),
+                        reason: EvaluationReason::Network,
+                    })
+            })
+            .collect())
+    }
+
+    async fn get_config(&self, config_name: &str, _user: &User) -> Result<ConfigEvaluationResult> {
+        let state = self.begin_evaluation()?;
+        Ok(state
+            .configs
+            .get(config_name)
+            .cloned()
+            .unwrap_or_else(|| ConfigEvaluationResult {
+                name: config_name.to_string(),
+                value: Value::Object(Default::default()),
+                rule_id: None,
+                group_name: None,
+                group: None,
+                secondary_exposures: Vec::new(),
+                reason: EvaluationReason::Network,
+            }))
+    }
+
+    async fn get_layer(&self, layer_name: &str, _user: &User) -> Result<LayerEvaluationResult> {
+        let state = self.begin_evaluation()?;
+        Ok(state
+            .layers
+            .get(layer_name)
+            .cloned()
+            .unwrap_or_else(|| LayerEvaluationResult {
+                name: layer_name.to_string(),
+                value: Value::Object(Default::default()),
+                rule_id: None,
+                group_name: None,
+                allocated_experiment_name: None,
+                explicit_parameters: Vec::new(),
+                secondary_exposures: Vec::new(),
+                undelegated_secondary_exposures: Vec::new(),
+                reason: EvaluationReason::Network,
+            }))
+    }
+
+    async fn download_config_specs(&self, _since_time: u64) -> Result<ConfigSpecs> {
+        let state = self.state();
+        match &state.error {
+            Some(error) => Err(error.clone()),
+            None => Ok(state.config_specs.clone()),
+        }
+    }
+
+    async fn log_events(
+        &self,
+        user: Option<&User>,
+        events: &[StatsigEvent],
+    ) -> Result<LogEventResponse> {
+        let mut state = self.state();
+        if let Some(error) = &state.error {
+            return Err(error.clone());
+        }
+
+        state
+            .logged_events
+            .extend(events.iter().cloned().map(|mut event| {
+                if event.user.is_none() {
+                    event.user = user.cloned();
+                }
+                event
+            }));
+        Ok(LogEventResponse { success: true })
+    }
+}
@@ -0,0 +1,386 @@
+//! Local gate and config overrides
+//!
+//! Overrides short-circuit evaluation before local evaluation, the cache and
+//! the batch layer. They are meant for QA, local development and incident
+//! kill-switches, and are never reported to Statsig as exposures.
+//!
+//! Overrides can also be loaded from a JSON or TOML file that is polled for
+//! changes and hot-reloaded. Overrides set through the client take
+//! precedence over the file.
+
+use crate::{
+    config::StatsigClientConfig,
+    error::{Result, StatsigError},
+    user::User,
+};
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+use std::time::SystemTime;
+use tracing::{info, warn};
+
+/// Which users an override applies to
+///
+/// When several overrides match a user, a `UserId` override wins over a
+/// `CustomId` override, which wins over a `Global` one.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum OverrideScope {
+    /// Every user
+    Global,
+    /// Users with this `user_id`
+    UserId(String),
+    /// Users with this value for the given custom ID type
+    CustomId { id_type: String, id: String },
+}
+
+impl OverrideScope {
+    pub fn user_id(user_id: impl Into<String>) -> Self {
+        Self::UserId(user_id.into())
+    }
+
+    pub fn custom_id(id_type: impl Into<String>, id: impl Into<String>) -> Self {
+        Self::CustomId {
+            id_type: id_type.into(),
+            id: id.into(),
+        }
+    }
+
+    /// Global scope for `None`, user ID scope otherwise
+    pub(crate) fn from_user_id(user_id: Option<&str>) -> Self {
+        user_id.map_or(Self::Global, Self::user_id)
+    }
+
+    /// Match priority for `user`, or `None` if the scope does not apply
+    fn priority(&self, user: &User) -> Option<u8> {
+        match self {
+            Self::UserId(user_id) => (user.user_id.as_ref() == Some(user_id)).then_some(2),
+            Self::CustomId { id_type, id } => user
+                .custom_ids
+                .as_ref()
+                .and_then(|ids| ids.get(id_type))
+                .filter(|value| *value == id)
+                .map(|_| 1),
+            Self::Global => Some(0),
+        }
+    }
+}
+
+/// The value an override forces
+#[derive(Debug, Clone, PartialEq)]
+pub enum OverrideValue {
+    Gate(bool),
+    Config(Value),
+}
+
+/// A registered override, as returned by `StatsigClient::list_overrides`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Override {
+    pub name: String,
+    pub scope: OverrideScope,
+    pub value: OverrideValue,
+}
+
+type ScopedOverrides<T> = HashMap<String, HashMap<OverrideScope, T>>;
+
+/// Contents of an overrides file
+///
+/// ```json
+/// { "gates": { "new_checkout": true }, "configs": { "ui_settings": { "theme": "dark" } } }
+/// ```
+#[derive(Debug, Default, Deserialize)]
+struct OverridesFile {
+    #[serde(default)]
+    gates: HashMap<String, bool>,
+    #[serde(default)]
+    configs: HashMap<String, Value>,
+}
+
+/// Global overrides loaded from the overrides file
+#[derive(Debug, Default)]
+struct FileOverrides {
+    gates: HashMap<String, bool>,
+    configs: HashMap<String, Value>,
+    error: Option<StatsigError>,
+}
+
+/// Thread-safe registry of gate and config overrides
+#[derive(Debug, Clone, Default)]
+pub(crate) struct OverrideStore {
+    gates: Arc<RwLock<ScopedOverrides<bool>>>,
+    configs: Arc<RwLock<ScopedOverrides<Value>>>,
+    file: Arc<RwLock<FileOverrides>>,
+}
+
+impl OverrideStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn set_gate(&self, name: String, scope: OverrideScope, value: bool) {
+        write(&self.gates)
+            .entry(name)
+            .or_default()
+            .insert(scope, value);
+    }
+
+    pub fn set_config(&self, name: String, scope: OverrideScope, value: Value) {
+        write(&self.configs)
+            .entry(name)
+            .or_default()
+            .insert(scope, value);
+    }
+
+    /// Removes a gate override, returning `true` if one existed
+    pub fn remove_gate(&self, name: &str, scope: &OverrideScope) -> bool {
+        remove(&self.gates, name, scope)
+    }
+
+    /// Removes a config override, returning `true` if one existed
+    pub fn remove_config(&self, name: &str, scope: &OverrideScope) -> bool {
+        remove(&self.configs, name, scope)
+    }
+
+    /// Removes every override set through the client; file overrides remain
+    pub fn clear(&self) {
+        write(&self.gates).clear();
+        write(&self.configs).clear();
+    }
+
+    pub fn gate(&self, name: &str, user: &User) -> Option<bool> {
+        lookup(&self.gates, name, user).or_else(|| read(&self.file).gates.get(name).copied())
+    }
+
+    pub fn config(&self, name: &str, user: &User) -> Option<Value> {
+        lookup(&self.configs, name, user).or_else(|| read(&self.file).configs.get(name).cloned())
+    }
+
+    /// Loads overrides from `path`, keeping the previous set if it cannot be parsed
+    pub fn load_file(&self, path: &Path) -> Result<()> {
+        let result = std::fs::read_to_string(path)
+            .map_err(|e| {
+                StatsigError::configuration(format!(
+                    "Failed to read overrides file {}: {}",
+                    path.display(),
+                    e
+                ))
+            })
+            .and_then(|contents| parse_overrides_file(path, &contents));
+
+        let mut file = write(&self.file);
+        match result {
+            Ok(parsed) => {
+                file.gates = parsed.gates;
+                file.configs = parsed.configs;
+                file.error = None;
+                Ok(())
+            }
+            Err(e) => {
+                file.error = Some(e.clone());
+                Err(e)
+            }
+        }
+    }
+
+    /// Error from the last failed load of the overrides file, if any
+    pub fn file_error(&self) -> Option<StatsigError> {
+        read(&self.file).error.clone()
+    }
+
+    /// All overrides, sorted by name and scope
+    pub fn list(&self) -> Vec<Override> {
+        let mut overrides = Vec::new();
+        for (name, scopes) in read(&self.gates).iter() {
+            for (scope, value) in scopes {
+                overrides.push(Override {
+                    name: name.clone(),
+                    scope: scope.clone(),
+                    value: OverrideValue::Gate(*value),
+                });
+            }
+        }
+        for (name, scopes) in read(&self.configs).iter() {
+            for (scope, value) in scopes {
+                overrides.push(Override {
+                    name: name.clone(),
+                    scope: scope.clone(),
+                    value: OverrideValue::Config(value.clone()),
+                });
+            }
+        }
+        overrides.sort_by(|a, b| (&a.name, &a.scope).cmp(&(&b.name, &b.scope)));
+        overrides
+    }
+}
+
+/// Parses TOML for `.toml` files and JSON otherwise
+fn parse_overrides_file(path: &Path, contents: &str) -> Result<OverridesFile> {
+    let is_toml = path
+        .extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("toml"));
+    let parsed = if is_toml {
+        toml::from_str(contents).map_err(|e| e.to_string())
+    } else {
+        serde_json::from_str(contents).map_err(|e| e.to_string())
+    };
+
+    parsed.map_err(|e| {
+        StatsigError::configuration(format!("Invalid overrides file {}: {}", path.display(), e))
+    })
+}
+
+/// Polls the overrides file and reloads it when it changes on disk
+pub(crate) struct OverrideFileWatcher {
+    store: OverrideStore,
+    path: PathBuf,
+    last_seen: Option<(SystemTime, u64)>,
+    shutdown_rx: tokio::sync::broadcast::Receiver<()>,
+}
+
+impl OverrideFileWatcher {
+    /// Creates a watcher; call before the initial load so no change is missed
+    pub fn new(
+        store: OverrideStore,
+        path: PathBuf,
+        shutdown_rx: tokio::sync::broadcast::Receiver<()>,
+    ) -> Self {
+        let last_seen = file_version(&path);
+        Self {
+            store,
+            path,
+            last_seen,
+            shutdown_rx,
+        }
+    }
+
+    /// Runs the poll loop until shutdown
+    pub async fn run(mut self, config: StatsigClientConfig) {
+        let mut interval = tokio::time::interval(config.overrides_poll_interval);
+        // The first tick completes immediately; the initial load happens at client creation
+        interval.tick().await;
+
+        loop {
+            tokio::select! {
+                _ = interval.tick() => self.poll(),
+                _ = self.shutdown_rx.recv() => {
+                    info!("Overrides file watcher shutting down");
+                    break;
+                }
+            }
+        }
+    }
+
+    fn poll(&mut self) {
+        let version = file_version(&self.path);
+        if version == self.last_seen {
+            return;
+        }
+        self.last_seen = version;
+
+        match self.store.load_file(&self.path) {
+            Ok(()) => info!("Reloaded overrides from {}", self.path.display()),
+            Err(e) => warn!("{}; keeping previous overrides", e),
+        }
+    }
+}
+
+/// Modification time and size, used to detect changes without reading the file
+fn file_version(path: &Path) -> Option<(SystemTime, u64)> {
+    let metadata = std::fs::metadata(path).ok()?;
+    Some((metadata.modified().ok()?, metadata.len()))
+}
+
+fn read<T>(lock: &RwLock<T>) -> std::sync::RwLockReadGuard<'_, T> {
+    lock.read().unwrap_or_else(|e| e.into_inner())
+}
+
+fn write<T>(lock: &RwLock<T>) -> std::sync::RwLockWriteGuard<'_, T> {
+    lock.write().unwrap_or_else(|e| e.into_inner())
+}
+
+fn remove<T>(overrides: &RwLock<ScopedOverrides<T>>, name: &str, scope: &OverrideScope) -> bool {
+    let mut overrides = write(overrides);
+    let Some(scopes) = overrides.get_mut(name) else {
+        return false;
+    };
+    let removed = scopes.remove(scope).is_some();
+    if scopes.is_empty() {
+        overrides.remove(name);
+    }
+    removed
+}
+
+/// Returns the highest priority override for `name` that applies to `user`
+fn lookup<T: Clone>(overrides: &RwLock<ScopedOverrides<T>>, name: &str, user: &User) -> Option<T> {
+    read(overrides)
+        .get(name)?
+        .iter()
+        .filter_map(|(scope, value)| {
+            scope
+                .priority(user)
+                .map(|priority| (priority, scope, value))
+        })
+        // Tie-break on scope so overlapping custom ID overrides resolve deterministically
+        .max_by(|a, b| (a.0, b.1).cmp(&(b.0, a.1)))
+        .map(|(_, _, value)| value.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn most_specific_scope_wins() {
+        let store = OverrideStore::new();
+        let user = User::builder()
+            .user_id("u1")
+            .custom_ids([("companyID", "acme")])
+            .build()
+            .unwrap();
+        let other = User::builder().user_id("u2").build().unwrap();
+
+        store.set_gate("gate".to_string(), OverrideScope::Global, false);
+        store.set_gate(
+            "gate".to_string(),
+            OverrideScope::custom_id("companyID", "acme"),
+            true,
+        );
+        assert_eq!(store.gate("gate", &user), Some(true));
+        assert_eq!(store.gate("gate", &other), Some(false));
+
+        store.set_gate("gate".to_string(), OverrideScope::user_id("u1"), false);
+        assert_eq!(store.gate("gate", &user), Some(false));
+
+        assert!(store.remove_gate("gate", &OverrideScope::user_id("u1")));
+        assert!(!store.remove_gate("gate", &OverrideScope::user_id("u1")));
+        assert_eq!(store.gate("gate", &user), Some(true));
+        assert_eq!(store.list().len(), 2);
+
+        store.clear();
+        assert_eq!(store.gate("gate", &user), None);
+    }
+
+    #[test]
+    fn invalid_file_keeps_previous_overrides() {
+        let dir = std::env::temp_dir().join(format!("statsig-overrides-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("overrides.toml");
+        let store = OverrideStore::new();
+        let user = User::builder().user_id("u1").build().unwrap();
+
+        std::fs::write(&path, "[gates]\nnew_checkout = true\n").unwrap();
+        store.load_file(&path).unwrap();
+        assert_eq!(store.gate("new_checkout", &user), Some(true));
+
+        std::fs::write(&path, "[gates\n").unwrap();
+        assert!(matches!(
+            store.load_file(&path),
+            Err(StatsigError::Configuration(_))
+        ));
+        assert!(store.file_error().is_some());
+        assert_eq!(store.gate("new_checkout", &user), Some(true));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,316 @@
+//! Client-side rate limiting for requests to the Statsig API
+//!
+//! A token bucket caps the sustained request rate and a semaphore caps the
+//! number of requests in flight. Requests over budget wait up to `max_wait`
+//! and otherwise fail with `StatsigError::RateLimited`, before anything is
+//! sent, so a traffic spike cannot exhaust the Statsig quota. Retries of an
+//! admitted request are not budgeted again.
+
+use crate::error::{Result, StatsigError};
+use bon::Builder;
+use http::Extensions;
+use reqwest::{Request, Response};
+use reqwest_middleware::{Middleware, Next};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::Semaphore;
+
+/// Outbound request budget
+#[derive(Debug, Clone, Builder)]
+pub struct RateLimitConfig {
+    /// Sustained requests per second; unlimited if unset
+    pub requests_per_second: Option<f64>,
+    /// Requests that can be sent back to back after an idle period
+    #[builder(default = 10)]
+    pub burst: u32,
+    /// Maximum concurrent requests; unlimited if unset
+    pub max_in_flight: Option<usize>,
+    /// How long a request may queue for budget before failing
+    #[builder(default = Duration::from_secs(1))]
+    pub max_wait: Duration,
+}
+
+impl RateLimitConfig {
+    pub fn validate(&self) -> Result<()> {
+        if let Some(rate) = self.requests_per_second {
+            if !(rate > 0.0 && rate.is_finite()) {
+                return Err(StatsigError::configuration(
+                    "Rate limit requests per second must be greater than 0",
+                ));
+            }
+        }
+
+        if self.burst == 0 {
+            return Err(StatsigError::configuration(
+                "Rate limit burst must be greater than 0",
+            ));
+        }
+
+        if self.max_in_flight == Some(0) {
+            return Err(StatsigError::configuration(
+                "Rate limit max in flight must be greater than 0",
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+/// Token bucket that hands out reservations, so queued requests are spaced
+/// evenly instead of racing for the next token
+#[derive(Debug)]
+struct TokenBucket {
+    rate: f64,
+    capacity: f64,
+    state: Mutex<BucketState>,
+}
+
+#[derive(Debug)]
+struct BucketState {
+    /// Available tokens; negative when future tokens are already reserved
+    tokens: f64,
+    last_refill: Instant,
+}
+
+impl TokenBucket {
+    fn new(rate: f64, burst: u32) -> Self {
+        Self {
+            rate,
+            capacity: burst as f64,
+            state: Mutex::new(BucketState {
+                tokens: burst as f64,
+                last_refill: Instant::now(),
+            }),
+        }
+    }
+
+    /// Reserves a token, returning how long to wait before using it, or the
+    /// expected wait if it exceeds `max_wait`
+    fn reserve(&self, max_wait: Duration) -> std::result::Result<Duration, Duration> {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        let now = Instant::now();
+        let refill = now.duration_since(state.last_refill).as_secs_f64() * self.rate;
+        state.tokens = (state.tokens + refill).min(self.capacity);
+        state.last_refill = now;
+
+        let wait = Duration::from_secs_f64((1.0 - state.tokens).max(0.0) / self.rate);
+        if wait > max_wait {
+            return Err(wait);
+        }
+        state.tokens -= 1.0;
+        Ok(wait)
+    }
+
+    /// Returns a reserved token that went unused
+    fn refund(&self) {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        state.tokens = (state.tokens + 1.0).min(self.capacity);
+    }
+}
+
+/// A reserved token, refunded when dropped unless the request was sent
+///
+/// Covers requests cancelled while waiting for their slot as well as those
+/// rejected by the in-flight limit.
+struct Reservation<'a> {
+    bucket: Option<&'a TokenBucket>,
+}
+
+impl Reservation<'_> {
+    /// Keeps the token once the request goes out
+    fn commit(mut self) {
+        self.bucket = None;
+    }
+}
+
+impl Drop for Reservation<'_> {
+    fn drop(&mut self) {
+        if let Some(bucket) = self.bucket {
+            bucket.refund();
+        }
+    }
+}
+
+/// Semaphore capping concurrent requests, which tracks request latency to
+/// estimate how long a queued request will wait for a slot
+#[derive(Debug)]
+struct InFlightLimit {
+    semaphore: Arc<Semaphore>,
+    permits: usize,
+    /// Requests waiting for a slot
+    waiting: AtomicUsize,
+    /// Moving average of request durations
+    latency: Mutex<Option<Duration>>,
+}
+
+impl InFlightLimit {
+    fn new(permits: usize) -> Self {
+        Self {
+            semaphore: Arc::new(Semaphore::new(permits)),
+            permits,
+            waiting: AtomicUsize::new(0),
+            latency: Mutex::new(None),
+        }
+    }
+
+    fn record_latency(&self, elapsed: Duration) {
+        let mut latency = self.latency.lock().unwrap_or_else(|e| e.into_inner());
+        *latency = Some(match *latency {
+            Some(average) => average.mul_f64(0.8) + elapsed.mul_f64(0.2),
+            None => elapsed,
+        });
+    }
+
+    /// Expected wait for a slot: each round of `permits` requests ahead in
+    /// the queue takes about one average request duration
+    fn expected_wait(&self, fallback: Duration) -> Duration {
+        let Some(latency) = *self.latency.lock().unwrap_or_else(|e| e.into_inner()) else {
+            return fallback;
+        };
+        let queued = self.waiting.load(Ordering::Relaxed) + 1;
+        latency * queued.div_ceil(self.permits) as u32
+    }
+}
+
+/// Registered outside the retry middleware, so a request's own retries
+/// cannot be rejected for lack of budget
+#[derive(Debug, Clone)]
+pub(crate) struct RateLimitMiddleware {
+    bucket: Option<Arc<TokenBucket>>,
+    in_flight: Option<Arc<InFlightLimit>>,
+    max_wait: Duration,
+}
+
+impl RateLimitMiddleware {
+    pub fn new(config: &RateLimitConfig) -> Self {
+        Self {
+            bucket: config
+                .requests_per_second
+                .map(|rate| Arc::new(TokenBucket::new(rate, config.burst))),
+            in_flight: config
+                .max_in_flight
+                .map(|permits| Arc::new(InFlightLimit::new(permits))),
+            max_wait: config.max_wait,
+        }
+    }
+}
+
+/// `StatsigError::RateLimited` with the wait rounded up to whole seconds
+fn rate_limited(wait: Duration) -> reqwest_middleware::Error {
+    let seconds = wait.as_secs() + u64::from(wait.subsec_nanos() > 0);
+    reqwest_middleware::Error::middleware(StatsigError::rate_limited(seconds.max(1)))
+}
+
+#[async_trait::async_trait]
+impl Middleware for RateLimitMiddleware {
+    async fn handle(
+        &self,
+        req: Request,
+        extensions: &mut Extensions,
+        next: Next<'_>,
+    ) -> reqwest_middleware::Result<Response> {
+        let deadline = Instant::now() + self.max_wait;
+
+        let mut reservation = Reservation { bucket: None };
+        if let Some(bucket) = &self.bucket {
+            let wait = bucket.reserve(self.max_wait).map_err(rate_limited)?;
+            reservation.bucket = Some(bucket);
+            if !wait.is_zero() {
+                tokio::time::sleep(wait).await;
+            }
+        }
+
+        let Some(limit) = &self.in_flight else {
+            reservation.commit();
+            return next.run(req, extensions).await;
+        };
+
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        limit.waiting.fetch_add(1, Ordering::Relaxed);
+        let acquired =
+            tokio::time::timeout(remaining, limit.semaphore.clone().acquire_owned()).await;
+        limit.waiting.fetch_sub(1, Ordering::Relaxed);
+        let _permit = match acquired {
+            Ok(Ok(permit)) => permit,
+            Ok(Err(_)) => {
+                return Err(reqwest_middleware::Error::middleware(
+                    StatsigError::internal("Rate limiter semaphore closed"),
+                ));
+            }
+            Err(_) => return Err(rate_limited(limit.expected_wait(self.max_wait))),
+        };
+
+        reservation.commit();
+        let started = Instant::now();
+        let result = next.run(req, extensions).await;
+        limit.record_latency(started.elapsed());
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bucket_spaces_requests_after_burst() {
+        let bucket = TokenBucket::new(10.0, 2);
+        let max_wait = Duration::from_millis(150);
+
+        assert_eq!(bucket.reserve(max_wait), Ok(Duration::ZERO));
+        assert_eq!(bucket.reserve(max_wait), Ok(Duration::ZERO));
+        // Each further token is reserved 100ms after the previous one
+        let wait = bucket.reserve(max_wait).unwrap();
+        assert!(wait > Duration::from_millis(90) && wait <= Duration::from_millis(100));
+        let wait = bucket.reserve(max_wait).unwrap_err();
+        assert!(wait > Duration::from_millis(190) && wait <= Duration::from_millis(200));
+    }
+
+    #[test]
+    fn refunded_tokens_are_reusable() {
+        let bucket = TokenBucket::new(0.001, 1);
+        assert_eq!(bucket.reserve(Duration::ZERO), Ok(Duration::ZERO));
+        assert!(bucket.reserve(Duration::ZERO).is_err());
+        bucket.refund();
+        assert_eq!(bucket.reserve(Duration::ZERO), Ok(Duration::ZERO));
+    }
+
+    #[tokio::test]
+    async fn cancelled_requests_refund_their_token() {
+        let config = RateLimitConfig::builder()
+            .requests_per_second(10.0)
+            .burst(1)
+            .build();
+        let middleware = RateLimitMiddleware::new(&config);
+        let bucket = middleware.bucket.clone().unwrap();
+        let client = reqwest_middleware::ClientBuilder::new(reqwest::Client::new())
+            .with(middleware)
+            .build();
+
+        assert_eq!(bucket.reserve(Duration::ZERO), Ok(Duration::ZERO));
+        // Dropped while sleeping for the reserved slot ~100ms out
+        let request = client.get("http://127.0.0.1:1").send();
+        assert!(
+            tokio::time::timeout(Duration::from_millis(20), request)
+                .await
+                .is_err()
+        );
+
+        // Without the refund the next slot would be ~200ms out
+        let wait = bucket.reserve(Duration::from_secs(1)).unwrap();
+        assert!(wait <= Duration::from_millis(100));
+    }
+
+    #[test]
+    fn in_flight_wait_scales_with_queue_and_latency() {
+        let limit = InFlightLimit::new(2);
+        let fallback = Duration::from_secs(1);
+        assert_eq!(limit.expected_wait(fallback), fallback);
+
+        limit.record_latency(Duration::from_millis(300));
+        assert_eq!(limit.expected_wait(fallback), Duration::from_millis(300));
+        limit.waiting.store(3, Ordering::Relaxed);
+        assert_eq!(limit.expected_wait(fallback), Duration::from_millis(600));
+    }
+}
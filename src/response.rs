@@ -6,6 +6,7 @@
 use crate::{
     api::{ConfigEvaluationResult, GateEvaluationResult},
     error::{Result, StatsigError},
+    events::ExposureEventMetadata,
 };
 use reqwest::Response;
 use reqwest::header::RETRY_AFTER;
@@ -45,6 +46,8 @@ impl ApiResponseHandler {
                 rule_id: Option<String>,
                 #[serde(rename = "group_name")]
                 group_name: Option<String>,
+                #[serde(default)]
+                secondary_exposures: Vec<ExposureEventMetadata>,
             }
 
             let map: std::collections::HashMap<String, GateEvaluationResultWire> =
@@ -59,6 +62,8 @@ impl ApiResponseHandler {
                     value: wire.value,
                     rule_id: wire.rule_id,
                     group_name: wire.group_name,
+                    secondary_exposures: wire.secondary_exposures,
+                    reason: Default::default(),
                 })
                 .collect())
         } else {
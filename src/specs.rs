@@ -0,0 +1,210 @@
+//! Config specs for local evaluation
+//!
+//! In local evaluation mode the client downloads the full rule set from
+//! `/v1/download_config_specs` and evaluates gates and configs in-process.
+//! This module holds the wire types for that payload, the [`SpecStore`] that
+//! keeps the latest indexed copy, and the [`SpecSyncer`] that refreshes it.
+
+use crate::{config::StatsigClientConfig, error::Result, transport::Transport};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use tracing::{debug, info, warn};
+
+/// The full rule set returned by `/v1/download_config_specs`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ConfigSpecs {
+    #[serde(default)]
+    pub feature_gates: Vec<ConfigSpec>,
+    #[serde(default)]
+    pub dynamic_configs: Vec<ConfigSpec>,
+    #[serde(default)]
+    pub layer_configs: Vec<ConfigSpec>,
+    #[serde(default)]
+    pub layers: HashMap<String, Vec<String>>,
+    #[serde(default)]
+    pub has_updates: bool,
+    #[serde(default)]
+    pub time: u64,
+}
+
+/// A single gate, dynamic config, experiment or layer definition
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConfigSpec {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub spec_type: String,
+    pub salt: String,
+    pub enabled: bool,
+    #[serde(rename = "defaultValue")]
+    pub default_value: serde_json::Value,
+    #[serde(default)]
+    pub rules: Vec<SpecRule>,
+    #[serde(rename = "idType", default)]
+    pub id_type: String,
+    #[serde(default)]
+    pub entity: Option<String>,
+    #[serde(rename = "explicitParameters", default)]
+    pub explicit_parameters: Option<Vec<String>>,
+    #[serde(default)]
+    pub version: Option<u64>,
+}
+
+/// A targeting rule within a [`ConfigSpec`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SpecRule {
+    pub name: String,
+    pub id: String,
+    #[serde(default)]
+    pub salt: Option<String>,
+    #[serde(rename = "passPercentage")]
+    pub pass_percentage: f64,
+    #[serde(default)]
+    pub conditions: Vec<SpecCondition>,
+    #[serde(rename = "returnValue", default)]
+    pub return_value: serde_json::Value,
+    #[serde(rename = "idType", default)]
+    pub id_type: String,
+    #[serde(rename = "groupName", default)]
+    pub group_name: Option<String>,
+    #[serde(rename = "configDelegate", default)]
+    pub config_delegate: Option<String>,
+    #[serde(rename = "isExperimentGroup", default)]
+    pub is_experiment_group: Option<bool>,
+}
+
+/// A single condition within a [`SpecRule`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SpecCondition {
+    #[serde(rename = "type")]
+    pub condition_type: String,
+    #[serde(rename = "targetValue", default)]
+    pub target_value: Option<serde_json::Value>,
+    #[serde(default)]
+    pub operator: Option<String>,
+    #[serde(default)]
+    pub field: Option<String>,
+    #[serde(rename = "additionalValues", default)]
+    pub additional_values: Option<HashMap<String, serde_json::Value>>,
+    #[serde(rename = "idType", default)]
+    pub id_type: String,
+}
+
+/// Config specs indexed by entity name for fast lookup during evaluation
+#[derive(Debug, Default)]
+pub(crate) struct IndexedSpecs {
+    pub gates: HashMap<String, ConfigSpec>,
+    pub configs: HashMap<String, ConfigSpec>,
+    pub layers: HashMap<String, ConfigSpec>,
+    pub time: u64,
+}
+
+impl From<ConfigSpecs> for IndexedSpecs {
+    fn from(specs: ConfigSpecs) -> Self {
+        let index = |list: Vec<ConfigSpec>| {
+            list.into_iter()
+                .map(|spec| (spec.name.clone(), spec))
+                .collect::<HashMap<_, _>>()
+        };
+
+        Self {
+            gates: index(specs.feature_gates),
+            configs: index(specs.dynamic_configs),
+            layers: index(specs.layer_configs),
+            time: specs.time,
+        }
+    }
+}
+
+/// Shared holder for the most recently downloaded config specs
+#[derive(Debug, Clone, Default)]
+pub(crate) struct SpecStore {
+    inner: Arc<RwLock<Option<Arc<IndexedSpecs>>>>,
+}
+
+impl SpecStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the current specs, or `None` if none have been loaded yet
+    pub fn get(&self) -> Option<Arc<IndexedSpecs>> {
+        self.inner.read().ok().and_then(|guard| guard.clone())
+    }
+
+    /// Replaces the current specs
+    pub fn set(&self, specs: ConfigSpecs) {
+        let indexed = Arc::new(IndexedSpecs::from(specs));
+        if let Ok(mut guard) = self.inner.write() {
+            *guard = Some(indexed);
+        }
+    }
+
+    /// Time of the last spec update reported by the server, or 0 if never synced
+    pub fn last_update_time(&self) -> u64 {
+        self.get().map(|specs| specs.time).unwrap_or(0)
+    }
+
+    /// Downloads specs newer than the current ones and stores them
+    ///
+    /// Returns `true` if the stored specs changed.
+    pub async fn refresh(&self, transport: &dyn Transport) -> Result<bool> {
+        let specs = transport
+            .download_config_specs(self.last_update_time())
+            .await?;
+
+        if !specs.has_updates {
+            debug!("Config specs are up to date");
+            return Ok(false);
+        }
+
+        info!("Config specs updated (time={})", specs.time);
+        self.set(specs);
+        Ok(true)
+    }
+}
+
+/// Periodically refreshes a [`SpecStore`] from the Statsig API
+pub(crate) struct SpecSyncer {
+    store: SpecStore,
+    /// Sync on the first tick instead of waiting a full interval
+    sync_immediately: bool,
+    shutdown_rx: tokio::sync::broadcast::Receiver<()>,
+}
+
+impl SpecSyncer {
+    pub fn new(
+        store: SpecStore,
+        sync_immediately: bool,
+        shutdown_rx: tokio::sync::broadcast::Receiver<()>,
+    ) -> Self {
+        Self {
+            store,
+            sync_immediately,
+            shutdown_rx,
+        }
+    }
+
+    /// Runs the sync loop until shutdown
+    pub async fn run(mut self, transport: Arc<dyn Transport>, config: StatsigClientConfig) {
+        let mut interval = tokio::time::interval(config.config_sync_interval);
+        if !self.sync_immediately {
+            // The first tick completes immediately; the initial sync happened at client creation
+            interval.tick().await;
+        }
+
+        loop {
+            tokio::select! {
+                _ = interval.tick() => {
+                    if let Err(e) = self.store.refresh(transport.as_ref()).await {
+                        warn!("Failed to sync config specs: {}", e);
+                    }
+                }
+                _ = self.shutdown_rx.recv() => {
+                    info!("Config spec syncer shutting down");
+                    break;
+                }
+            }
+        }
+    }
+}
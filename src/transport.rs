@@ -1,9 +1,15 @@
 use crate::{
-    api::{ConfigEvaluationResult, GateEvaluationResult, StatsigMetadata},
+    api::{
+        ConfigEvaluationResult, EvaluationReason, GateEvaluationResult, LayerEvaluationResult,
+        StatsigMetadata,
+    },
+    circuit_breaker::{CircuitBreaker, CircuitBreakerMiddleware, CircuitState},
     config::StatsigClientConfig,
     error::{Result, StatsigError},
     events::{LogEventResponse, StatsigEvent},
+    rate_limit::RateLimitMiddleware,
     response::ApiResponseHandler,
+    specs::ConfigSpecs,
     user::User,
 };
 use backoff::backoff::Backoff;
@@ -16,6 +22,8 @@ use reqwest_retry::{
     RetryDecision, RetryPolicy, RetryTransientMiddleware, Retryable, RetryableStrategy,
 };
 use serde::Serialize;
+use std::fmt::Debug;
+use std::sync::Arc;
 use std::time::{Duration, SystemTime};
 use thiserror::Error;
 
@@ -25,13 +33,53 @@ enum TransportMiddlewareError {
     UncloneableRequest,
 }
 
+/// Backend used by the client to evaluate entities and deliver events
+///
+/// [`StatsigTransport`] talks to the Statsig HTTP API. Implement this trait
+/// to route requests elsewhere, or use [`crate::MockTransport`] in tests.
+#[async_trait::async_trait]
+pub trait Transport: Debug + Send + Sync {
+    /// Evaluates several gates for one user
+    async fn check_gates(
+        &self,
+        gate_names: Vec<String>,
+        user: &User,
+    ) -> Result<Vec<GateEvaluationResult>>;
+
+    /// Evaluates a dynamic config or experiment for one user
+    async fn get_config(&self, config_name: &str, user: &User) -> Result<ConfigEvaluationResult>;
+
+    /// Evaluates a layer for one user
+    async fn get_layer(&self, layer_name: &str, user: &User) -> Result<LayerEvaluationResult>;
+
+    /// Downloads rule specs updated after `since_time`
+    async fn download_config_specs(&self, since_time: u64) -> Result<ConfigSpecs>;
+
+    /// Delivers a batch of events
+    async fn log_events(
+        &self,
+        user: Option<&User>,
+        events: &[StatsigEvent],
+    ) -> Result<LogEventResponse>;
+
+    /// State of the circuit breaker, if the transport has one
+    fn circuit_state(&self) -> Option<CircuitState> {
+        None
+    }
+}
+
+/// [`Transport`] backed by the Statsig HTTP API
 #[derive(Debug, Clone)]
 pub struct StatsigTransport {
+    /// Client for evaluation requests, guarded by the circuit breaker
+    evaluation_client: ClientWithMiddleware,
+    /// Client for event delivery and config spec downloads
     client: ClientWithMiddleware,
     base_url: String,
     events_base_url: String,
     api_key: String,
     exposure_logging_disabled: bool,
+    circuit_breaker: Option<Arc<CircuitBreaker>>,
 }
 
 impl StatsigTransport {
@@ -44,32 +92,55 @@ impl StatsigTransport {
                 StatsigError::configuration(format!("Failed to create HTTP client: {}", e))
             })?;
 
-        let retry_policy = BackoffRetryPolicy::new(config.retry_attempts, config.retry_delay);
-        let retry_transient = RetryTransientMiddleware::new_with_policy_and_strategy(
-            retry_policy,
-            No429RetryStrategy,
-        );
-
-        let client = ClientBuilder::new(inner)
-            .with(RateLimitRetryMiddleware::new(
-                config.retry_attempts,
-                config.retry_delay,
-            ))
-            .with(retry_transient)
-            .build();
+        let circuit_breaker = config
+            .circuit_breaker
+            .clone()
+            .map(|breaker_config| Arc::new(CircuitBreaker::new(breaker_config)));
+        // One limiter shared by both stacks, so every request draws on the same budget
+        let rate_limit = config.rate_limit.as_ref().map(RateLimitMiddleware::new);
+        let build_client = |breaker: Option<&Arc<CircuitBreaker>>| {
+            let mut builder = ClientBuilder::new(inner.clone());
+            if let Some(breaker) = breaker {
+                builder = builder.with(CircuitBreakerMiddleware::new(breaker.clone()));
+            }
+            // Budgeted once per request, outside the retries
+            if let Some(rate_limit) = &rate_limit {
+                builder = builder.with(rate_limit.clone());
+            }
+
+            let retry_policy = BackoffRetryPolicy::new(config.retry_attempts, config.retry_delay);
+            builder
+                .with(RateLimitRetryMiddleware::new(
+                    config.retry_attempts,
+                    config.retry_delay,
+                ))
+                .with(RetryTransientMiddleware::new_with_policy_and_strategy(
+                    retry_policy,
+                    No429RetryStrategy,
+                ))
+                .build()
+        };
 
         Ok(Self {
-            client,
+            // Only evaluations go through the breaker, so a failing events
+            // host or spec download cannot fail gate and config checks
+            evaluation_client: build_client(circuit_breaker.as_ref()),
+            client: build_client(None),
             base_url: config.base_url.clone(),
             events_base_url: config.events_base_url.clone(),
             api_key: config.api_key.clone(),
             exposure_logging_disabled: config.exposure_logging_disabled,
+            circuit_breaker,
         })
     }
 
-    async fn post_sdk<T: Serialize>(&self, path: &str, body: &T) -> Result<Response> {
-        let response = self
-            .client
+    async fn post_sdk<T: Serialize>(
+        &self,
+        client: &ClientWithMiddleware,
+        path: &str,
+        body: &T,
+    ) -> Result<Response> {
+        let response = client
             .post(format!("{}{}", self.base_url, path))
             .header("statsig-api-key", &self.api_key)
             .header("Content-Type", "application/json")
@@ -99,8 +170,11 @@ impl StatsigTransport {
 
         Ok(response)
     }
+}
 
-    pub async fn check_gates(
+#[async_trait::async_trait]
+impl Transport for StatsigTransport {
+    async fn check_gates(
         &self,
         gate_names: Vec<String>,
         user: &User,
@@ -117,20 +191,18 @@ impl StatsigTransport {
         let request_body = CheckGateRequest {
             gate_names,
             user,
-            statsig_metadata: StatsigMetadata::default()
-                .with_exposure_logging_disabled(self.exposure_logging_disabled),
+            // Exposures are logged client-side for every evaluation served
+            statsig_metadata: StatsigMetadata::default().with_exposure_logging_disabled(true),
         };
 
-        let response = self.post_sdk("/v1/check_gate", &request_body).await?;
+        let response = self
+            .post_sdk(&self.evaluation_client, "/v1/check_gate", &request_body)
+            .await?;
 
         ApiResponseHandler::handle_gate_response(response).await
     }
 
-    pub async fn get_config(
-        &self,
-        config_name: &str,
-        user: &User,
-    ) -> Result<ConfigEvaluationResult> {
+    async fn get_config(&self, config_name: &str, user: &User) -> Result<ConfigEvaluationResult> {
         #[derive(Serialize)]
         struct GetConfigRequest<'a> {
             #[serde(rename = "configName")]
@@ -143,18 +215,66 @@ impl StatsigTransport {
         let request_body = GetConfigRequest {
             config_name: config_name.to_string(),
             user,
+            // Exposures are logged client-side for every evaluation served
+            statsig_metadata: StatsigMetadata::default().with_exposure_logging_disabled(true),
+        };
+
+        let response = self
+            .post_sdk(&self.evaluation_client, "/v1/get_config", &request_body)
+            .await?;
+
+        ApiResponseHandler::handle_config_response(response).await
+    }
+
+    async fn get_layer(&self, layer_name: &str, user: &User) -> Result<LayerEvaluationResult> {
+        #[derive(Serialize)]
+        struct GetLayerRequest<'a> {
+            #[serde(rename = "layerName")]
+            layer_name: String,
+            user: &'a User,
+            #[serde(rename = "statsigMetadata")]
+            statsig_metadata: StatsigMetadata,
+        }
+
+        let request_body = GetLayerRequest {
+            layer_name: layer_name.to_string(),
+            user,
+            // Layer exposures are logged client-side per accessed parameter
+            statsig_metadata: StatsigMetadata::default().with_exposure_logging_disabled(true),
+        };
+
+        let response = self
+            .post_sdk(&self.evaluation_client, "/v1/get_layer", &request_body)
+            .await?;
+
+        ApiResponseHandler::handle(response).await
+    }
+
+    async fn download_config_specs(&self, since_time: u64) -> Result<ConfigSpecs> {
+        #[derive(Serialize)]
+        struct DownloadConfigSpecsRequest {
+            #[serde(rename = "sinceTime")]
+            since_time: u64,
+            #[serde(rename = "statsigMetadata")]
+            statsig_metadata: StatsigMetadata,
+        }
+
+        let request_body = DownloadConfigSpecsRequest {
+            since_time,
             statsig_metadata: StatsigMetadata::default()
                 .with_exposure_logging_disabled(self.exposure_logging_disabled),
         };
 
-        let response = self.post_sdk("/v1/get_config", &request_body).await?;
+        let response = self
+            .post_sdk(&self.client, "/v1/download_config_specs", &request_body)
+            .await?;
 
-        ApiResponseHandler::handle_config_response(response).await
+        ApiResponseHandler::handle(response).await
     }
 
-    pub async fn log_events(
+    async fn log_events(
         &self,
-        user: &User,
+        user: Option<&User>,
         events: &[StatsigEvent],
     ) -> Result<LogEventResponse> {
         #[derive(Serialize)]
@@ -168,7 +288,7 @@ impl StatsigTransport {
 
         let request_body = LogEventRequest {
             events,
-            user: Some(user),
+            user,
             statsig_metadata: Some(
                 StatsigMetadata::default()
                     .with_exposure_logging_disabled(self.exposure_logging_disabled),
@@ -179,6 +299,88 @@ impl StatsigTransport {
 
         ApiResponseHandler::handle(response).await
     }
+
+    fn circuit_state(&self) -> Option<CircuitState> {
+        self.circuit_breaker.as_ref().map(|breaker| breaker.state())
+    }
+}
+
+/// [`Transport`] used in local mode, which never contacts Statsig
+///
+/// Entities without an override evaluate to the configured offline defaults
+/// and events are discarded.
+#[derive(Debug, Clone)]
+pub(crate) struct LocalModeTransport {
+    default_gate_value: bool,
+    default_config_value: serde_json::Value,
+}
+
+impl LocalModeTransport {
+    pub fn new(config: &StatsigClientConfig) -> Self {
+        Self {
+            default_gate_value: config.offline_default_gate_value,
+            default_config_value: config.offline_default_config_value.clone(),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl Transport for LocalModeTransport {
+    async fn check_gates(
+        &self,
+        gate_names: Vec<String>,
+        _user: &User,
+    ) -> Result<Vec<GateEvaluationResult>> {
+        Ok(gate_names
+            .into_iter()
+            .map(|name| GateEvaluationResult {
+                name,
+                value: self.default_gate_value,
+                rule_id: None,
+                group_name: None,
+                secondary_exposures: Vec::new(),
+                reason: EvaluationReason::Default,
+            })
+            .collect())
+    }
+
+    async fn get_config(&self, config_name: &str, _user: &User) -> Result<ConfigEvaluationResult> {
+        Ok(ConfigEvaluationResult {
+            name: config_name.to_string(),
+            value: self.default_config_value.clone(),
+            rule_id: None,
+            group_name: None,
+            group: None,
+            secondary_exposures: Vec::new(),
+            reason: EvaluationReason::Default,
+        })
+    }
+
+    async fn get_layer(&self, layer_name: &str, _user: &User) -> Result<LayerEvaluationResult> {
+        Ok(LayerEvaluationResult {
+            name: layer_name.to_string(),
+            value: serde_json::Value::Object(Default::default()),
+            rule_id: None,
+            group_name: None,
+            allocated_experiment_name: None,
+            explicit_parameters: Vec::new(),
+            secondary_exposures: Vec::new(),
+            undelegated_secondary_exposures: Vec::new(),
+            reason: EvaluationReason::Default,
+        })
+    }
+
+    async fn download_config_specs(&self, _since_time: u64) -> Result<ConfigSpecs> {
+        Ok(ConfigSpecs::default())
+    }
+
+    async fn log_events(
+        &self,
+        _user: Option<&User>,
+        _events: &[StatsigEvent],
+    ) -> Result<LogEventResponse> {
+        Ok(LogEventResponse { success: true })
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -250,6 +452,97 @@ mod tests {
         assert_eq!(parse_retry_after("2"), Some(Duration::from_secs(2)));
     }
 
+    #[tokio::test]
+    async fn circuit_breaker_fails_fast_once_open() {
+        let config = StatsigClientConfig::builder()
+            .api_key("test_key")
+            // Nothing listens on the discard port, so connections are refused
+            .base_url("http://127.0.0.1:9".to_string())
+            .retry_attempts(1)
+            .retry_delay(Duration::from_millis(1))
+            .circuit_breaker(
+                crate::CircuitBreakerConfig::builder()
+                    .consecutive_failures(2)
+                    .build(),
+            )
+            .build();
+        let transport = StatsigTransport::new(&config).unwrap();
+        let user = User::builder().user_id("u1").build().unwrap();
+
+        for _ in 0..2 {
+            let result = transport.check_gates(vec!["gate".to_string()], &user).await;
+            assert!(matches!(result, Err(StatsigError::Network(_))));
+        }
+        assert_eq!(transport.circuit_state(), Some(CircuitState::Open));
+        let result = transport.check_gates(vec!["gate".to_string()], &user).await;
+        assert!(matches!(result, Err(StatsigError::CircuitOpen { .. })));
+    }
+
+    #[tokio::test]
+    async fn event_failures_do_not_open_the_circuit_breaker() {
+        let config = StatsigClientConfig::builder()
+            .api_key("test_key")
+            .base_url("http://127.0.0.1:9".to_string())
+            .events_base_url("http://127.0.0.1:9".to_string())
+            .retry_attempts(1)
+            .retry_delay(Duration::from_millis(1))
+            .circuit_breaker(
+                crate::CircuitBreakerConfig::builder()
+                    .consecutive_failures(1)
+                    .build(),
+            )
+            .build();
+        let transport = StatsigTransport::new(&config).unwrap();
+        let user = User::builder().user_id("u1").build().unwrap();
+        let events = vec![
+            StatsigEvent::builder()
+                .event_name("page_view".to_string())
+                .build(),
+        ];
+
+        for _ in 0..3 {
+            assert!(transport.log_events(Some(&user), &events).await.is_err());
+            assert!(transport.download_config_specs(0).await.is_err());
+        }
+        assert_eq!(transport.circuit_state(), Some(CircuitState::Closed));
+
+        // Evaluations still reach the network instead of failing fast
+        let result = transport.check_gates(vec!["gate".to_string()], &user).await;
+        assert!(matches!(result, Err(StatsigError::Network(_))));
+    }
+
+    #[tokio::test]
+    async fn rate_limit_rejects_requests_over_budget() {
+        let config = StatsigClientConfig::builder()
+            .api_key("test_key")
+            .base_url("http://127.0.0.1:9".to_string())
+            .retry_attempts(1)
+            .retry_delay(Duration::from_millis(1))
+            .rate_limit(
+                crate::RateLimitConfig::builder()
+                    .requests_per_second(0.5)
+                    .burst(2)
+                    .max_wait(Duration::ZERO)
+                    .build(),
+            )
+            .build();
+        let transport = StatsigTransport::new(&config).unwrap();
+        let user = User::builder().user_id("u1").build().unwrap();
+
+        // Retries are not budgeted, so each request uses one token of the burst
+        for _ in 0..2 {
+            let result = transport.check_gates(vec!["gate".to_string()], &user).await;
+            assert!(matches!(result, Err(StatsigError::Network(_))));
+        }
+        let result = transport.check_gates(vec!["gate".to_string()], &user).await;
+        assert!(matches!(
+            result,
+            Err(StatsigError::RateLimited {
+                retry_after_seconds: 2
+            })
+        ));
+    }
+
     #[test]
     fn parse_retry_after_http_date() {
         let when = SystemTime::now() + Duration::from_secs(2);
@@ -155,27 +155,127 @@ impl User {
         self.user_id.as_deref()
     }
 
-    /// Generates a consistent hash for the user used in cache keys and batch grouping
+    /// Generates a canonical fingerprint of the user used in cache keys and batch grouping
     ///
-    /// This hash includes all user identifiers to ensure consistent cache behavior
-    /// across different user representations with the same logical identity.
+    /// Every field that targeting rules can read is included, and map entries
+    /// are hashed in sorted key order so equal users always hash the same.
     pub fn hash_for_cache(&self) -> String {
         let mut hasher = Sha256::new();
 
-        if let Some(user_id) = &self.user_id {
-            hasher.update(user_id.as_bytes());
+        hash_str_field(&mut hasher, "userID", self.user_id.as_deref());
+        hash_str_field(&mut hasher, "email", self.email.as_deref());
+        hash_str_field(&mut hasher, "ip", self.ip.as_deref());
+        hash_str_field(&mut hasher, "userAgent", self.user_agent.as_deref());
+        hash_str_field(&mut hasher, "country", self.country.as_deref());
+        hash_str_field(&mut hasher, "locale", self.locale.as_deref());
+        hash_str_field(&mut hasher, "appVersion", self.app_version.as_deref());
+        hash_map_field(&mut hasher, "custom", self.custom.as_ref(), hash_json);
+        hash_map_field(
+            &mut hasher,
+            "privateAttributes",
+            self.private_attributes.as_ref(),
+            hash_json,
+        );
+        hash_map_field(
+            &mut hasher,
+            "customIDs",
+            self.custom_ids.as_ref(),
+            |hasher, value| hash_bytes(hasher, value.as_bytes()),
+        );
+        hash_str_field(
+            &mut hasher,
+            "statsigEnvironment",
+            self.statsig_environment
+                .as_ref()
+                .map(|environment| environment.tier.as_str()),
+        );
+
+        hex::encode(hasher.finalize())
+    }
+}
+
+impl EnvironmentTier {
+    /// Returns the tier name as sent to Statsig
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EnvironmentTier::Production => "production",
+            EnvironmentTier::Staging => "staging",
+            EnvironmentTier::Development => "development",
         }
-        if let Some(email) = &self.email {
-            hasher.update(email.as_bytes());
+    }
+}
+
+/// Hashes length-prefixed bytes so adjacent values cannot run together
+fn hash_bytes(hasher: &mut Sha256, bytes: &[u8]) {
+    hasher.update((bytes.len() as u64).to_be_bytes());
+    hasher.update(bytes);
+}
+
+fn hash_str_field(hasher: &mut Sha256, name: &str, value: Option<&str>) {
+    hash_bytes(hasher, name.as_bytes());
+    match value {
+        Some(value) => {
+            hasher.update([1]);
+            hash_bytes(hasher, value.as_bytes());
         }
-        if let Some(custom_ids) = &self.custom_ids {
-            for (key, value) in custom_ids {
-                hasher.update(key.as_bytes());
-                hasher.update(value.as_bytes());
+        None => hasher.update([0]),
+    }
+}
+
+fn hash_map_field<V>(
+    hasher: &mut Sha256,
+    name: &str,
+    map: Option<&HashMap<String, V>>,
+    hash_value: impl Fn(&mut Sha256, &V),
+) {
+    hash_bytes(hasher, name.as_bytes());
+    match map {
+        Some(map) => {
+            hasher.update([1]);
+            hasher.update((map.len() as u64).to_be_bytes());
+            let mut entries: Vec<_> = map.iter().collect();
+            entries.sort_by(|a, b| a.0.cmp(b.0));
+            for (key, value) in entries {
+                hash_bytes(hasher, key.as_bytes());
+                hash_value(hasher, value);
             }
         }
+        None => hasher.update([0]),
+    }
+}
 
-        hex::encode(hasher.finalize())
+/// Hashes a JSON value with object keys in sorted order
+fn hash_json(hasher: &mut Sha256, value: &serde_json::Value) {
+    use serde_json::Value;
+
+    match value {
+        Value::Null => hasher.update([0]),
+        Value::Bool(b) => hasher.update([1, *b as u8]),
+        Value::Number(n) => {
+            hasher.update([2]);
+            hash_bytes(hasher, n.to_string().as_bytes());
+        }
+        Value::String(s) => {
+            hasher.update([3]);
+            hash_bytes(hasher, s.as_bytes());
+        }
+        Value::Array(items) => {
+            hasher.update([4]);
+            hasher.update((items.len() as u64).to_be_bytes());
+            for item in items {
+                hash_json(hasher, item);
+            }
+        }
+        Value::Object(map) => {
+            hasher.update([5]);
+            hasher.update((map.len() as u64).to_be_bytes());
+            let mut entries: Vec<_> = map.iter().collect();
+            entries.sort_by(|a, b| a.0.cmp(b.0));
+            for (key, value) in entries {
+                hash_bytes(hasher, key.as_bytes());
+                hash_json(hasher, value);
+            }
+        }
     }
 }
 
@@ -186,3 +286,33 @@ impl Default for StatsigEnvironment {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cache_hash_covers_targeting_fields_and_is_order_stable() {
+        let base = || User::builder().user_id("u1");
+        let us = base().country("US").build().unwrap();
+        let ca = base().country("CA").build().unwrap();
+        assert_ne!(us.hash_for_cache(), ca.hash_for_cache());
+
+        let free = base()
+            .custom([("plan", serde_json::json!("free"))])
+            .build()
+            .unwrap();
+        let pro = base()
+            .custom([("plan", serde_json::json!("pro"))])
+            .build()
+            .unwrap();
+        assert_ne!(free.hash_for_cache(), pro.hash_for_cache());
+
+        let ids: Vec<_> = (0..32)
+            .map(|i| (format!("id{i}"), format!("v{i}")))
+            .collect();
+        let forward = base().custom_ids(ids.clone()).build().unwrap();
+        let reverse = base().custom_ids(ids.into_iter().rev()).build().unwrap();
+        assert_eq!(forward.hash_for_cache(), reverse.hash_for_cache());
+    }
+}